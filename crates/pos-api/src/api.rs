@@ -3,7 +3,8 @@
 pub struct Config {
     /// The directory where pos data files are created.
     /// Path must be accessible by the server.
-    /// data.json will be created in the pos data folder. It will contain the index of the pow solution.
+    /// A <job_id>.data.json metadata file is created in the pos data folder for each job. It contains
+    /// the job's compute params, its data files index ranges and the index of the pow solution.
     #[prost(string, tag = "1")]
     pub data_dir: ::prost::alloc::string::String,
     /// number of indexes to compute per gpu compute cycle. e.g. 1024^4
@@ -24,6 +25,53 @@ pub struct Config {
     /// scrypt param
     #[prost(uint32, tag = "7")]
     pub p: u32,
+    /// number of compute cycles between job data syncs to disk and job checkpoint updates
+    #[prost(uint32, tag = "8")]
+    pub checkpoint_interval: u32,
+    /// Max size in bytes of a pos data file. A job's data is written to numbered files <job_id>_0.pos, <job_id>_1.pos...
+    /// The index range of each file is listed in the job's metadata file. 0 for a single <job_id>.pos data file per job.
+    #[prost(uint64, tag = "9")]
+    pub max_file_size: u64,
+    /// A job's pow solution search stops with a POW_SEARCH_LIMIT error after searching pow_search_factor times the
+    /// expected number of indexes for the job's pow difficulty. 0 for no limit.
+    #[prost(uint32, tag = "10")]
+    pub pow_search_factor: u32,
+    /// max time in seconds of a job's pow solution search. 0 for no limit.
+    #[prost(uint64, tag = "11")]
+    pub pow_search_timeout: u64,
+    /// Number of times a failed compute cycle is retried on its provider before the rest of the stripe fails over to
+    /// another provider. A cycle fails when the compute lib returns an error or computes fewer hashes than requested.
+    #[prost(uint32, tag = "12")]
+    pub compute_retries: u32,
+    /// delay before the first retry of a failed cycle. Doubled for each following retry
+    #[prost(uint32, tag = "13")]
+    pub retry_backoff_ms: u32,
+    /// Number of failovers from a provider, without a job completed by the provider in between, after which the
+    /// provider is quarantined and no longer used for jobs. 0 to never quarantine providers.
+    #[prost(uint32, tag = "14")]
+    pub quarantine_failures: u32,
+}
+/// Pos compute params of a job
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ComputeParams {
+    /// scrypt salt
+    #[prost(bytes = "vec", tag = "1")]
+    pub salt: ::prost::alloc::vec::Vec<u8>,
+    /// scrypt param
+    #[prost(uint32, tag = "2")]
+    pub n: u32,
+    /// scrypt param
+    #[prost(uint32, tag = "3")]
+    pub r: u32,
+    /// scrypt param
+    #[prost(uint32, tag = "4")]
+    pub p: u32,
+    /// should be 8 for now
+    #[prost(uint32, tag = "5")]
+    pub bits_per_index: u32,
+    /// number of indexes to compute per gpu compute cycle
+    #[prost(uint64, tag = "6")]
+    pub indexes_per_compute_cycle: u64,
 }
 /// A pos compute provider such as a GPU or a CPU
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -34,6 +82,15 @@ pub struct Provider {
     pub model: ::prost::alloc::string::String,
     #[prost(enumeration = "provider::Class", tag = "3")]
     pub class: i32,
+    /// false when the provider was disabled and isn't used for jobs
+    #[prost(bool, tag = "4")]
+    pub enabled: bool,
+    /// hash rate measured by benchmarking the provider on startup when the benchmark_indexes config is set. 0 when it wasn't benchmarked
+    #[prost(uint64, tag = "5")]
+    pub hashes_per_sec: u64,
+    /// true when the provider kept failing and isn't used for jobs until it is enabled
+    #[prost(bool, tag = "6")]
+    pub quarantined: bool,
 }
 /// Nested message and enum types in `Provider`.
 pub mod provider {
@@ -45,6 +102,70 @@ pub mod provider {
         X86 = 2,
     }
 }
+/// Providers which may compute a job. A job with an affinity is queued until a matching provider is idle
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProviderAffinity {
+    #[prost(oneof = "provider_affinity::Affinity", tags = "1, 2, 3")]
+    pub affinity: ::core::option::Option<provider_affinity::Affinity>,
+}
+/// Nested message and enum types in `ProviderAffinity`.
+pub mod provider_affinity {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Affinity {
+        /// a specific provider
+        #[prost(uint32, tag = "1")]
+        ProviderId(u32),
+        /// providers of a class as reported in their Provider.class
+        #[prost(enumeration = "super::provider::Class", tag = "2")]
+        Class(i32),
+        /// providers with a benchmarked hash rate of at least min_hashes_per_sec
+        #[prost(uint64, tag = "3")]
+        MinHashesPerSec(u64),
+    }
+}
+/// Selects compute providers by id, model or class
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProviderFilter {
+    #[prost(oneof = "provider_filter::Filter", tags = "1, 2, 3")]
+    pub filter: ::core::option::Option<provider_filter::Filter>,
+}
+/// Nested message and enum types in `ProviderFilter`.
+pub mod provider_filter {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Filter {
+        /// provider id
+        #[prost(uint32, tag = "1")]
+        Id(u32),
+        /// case insensitive model pattern where * matches any text. e.g. "*intel*"
+        #[prost(string, tag = "2")]
+        Model(::prost::alloc::string::String),
+        /// CPU, CUDA or VULKAN
+        #[prost(string, tag = "3")]
+        Class(::prost::alloc::string::String),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EnableProviderRequest {
+    #[prost(message, optional, tag = "1")]
+    pub filter: ::core::option::Option<ProviderFilter>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EnableProviderResponse {
+    /// the providers matching the filter
+    #[prost(message, repeated, tag = "1")]
+    pub providers: ::prost::alloc::vec::Vec<Provider>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DisableProviderRequest {
+    #[prost(message, optional, tag = "1")]
+    pub filter: ::core::option::Option<ProviderFilter>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DisableProviderResponse {
+    /// the providers matching the filter
+    #[prost(message, repeated, tag = "1")]
+    pub providers: ::prost::alloc::vec::Vec<Provider>,
+}
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetProvidersRequest {}
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -63,6 +184,9 @@ pub struct GetConfigResponse {
 pub struct SetConfigRequest {
     #[prost(message, optional, tag = "1")]
     pub config: ::core::option::Option<Config>,
+    /// set the config while jobs are running or queued. Jobs keep the compute params they were submitted with
+    #[prost(bool, tag = "2")]
+    pub force: bool,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SetConfigResponse {}
@@ -83,7 +207,7 @@ pub struct Job {
     /// unique job id generated by the service
     #[prost(uint64, tag = "1")]
     pub id: u64,
-    /// bits written to the data file (at dir/id.post) (each index 1 byte)
+    /// bits written to the job's data files by all of its stripes (each index 1 byte)
     #[prost(uint64, tag = "2")]
     pub bits_written: u64,
     /// final requested size in bits
@@ -107,21 +231,63 @@ pub struct Job {
     /// client provided friendly name e.g. 'my pos 1'
     #[prost(string, tag = "11")]
     pub friendly_name: ::prost::alloc::string::String,
-    /// unique client id (input to pos algorithm)
+    /// unique client id (input to pos algorithm), up to 32 bytes. Shorter ids are padded with zeros
     #[prost(bytes = "vec", tag = "12")]
     pub client_id: ::prost::alloc::vec::Vec<u8>,
-    /// compute provider processor id which executed this job - useful for debugging when job fail
+    /// compute provider processor id which executed this job's first stripe - useful for debugging when job fail
     #[prost(uint32, tag = "13")]
     pub compute_provider_id: u32,
-    /// pow target difficulty, 32 bytes
+    /// pow target difficulty, 32 bytes. Required when computing a pow solution
     #[prost(bytes = "vec", tag = "14")]
     pub pow_difficulty: ::prost::alloc::vec::Vec<u8>,
-    /// index of the pow solution index. Only available for a completed job. u64:MAX means no solution.
+    /// index of the first pow solution. u64:MAX means no solution.
     #[prost(uint64, tag = "15")]
     pub pow_solution_index: u64,
     /// when false, job will only compute leaves. When true, job will compute leaves and a pow solution
     #[prost(bool, tag = "16")]
     pub compute_pow_solution: bool,
+    /// the job's index range split to stripes which are computed in parallel by idle providers
+    #[prost(message, repeated, tag = "17")]
+    pub stripes: ::prost::alloc::vec::Vec<JobStripe>,
+    /// next index to search for a pow solution once all stripes are completed. 0 when no search was needed
+    #[prost(uint64, tag = "18")]
+    pub pow_search_index: u64,
+    /// job's kind
+    #[prost(enumeration = "job::Kind", tag = "19")]
+    pub kind: i32,
+    /// number of hashes computed by the job's pow solution search
+    #[prost(uint64, tag = "20")]
+    pub pow_hashes_searched: u64,
+    /// indexes of the pow solutions found by the job in ascending order. pow_solution_index is the first solution
+    #[prost(uint64, repeated, tag = "21")]
+    pub pow_solutions: ::prost::alloc::vec::Vec<u64>,
+    /// number of pow solutions to find. The job finds the first pow_solutions_count solutions
+    #[prost(uint32, tag = "22")]
+    pub pow_solutions_count: u32,
+    /// when true, the job finds all the pow solutions in its indexes range
+    #[prost(bool, tag = "23")]
+    pub all_pow_solutions: bool,
+    /// compute params of the job. Set when the job is submitted and never changed by config updates
+    #[prost(message, optional, tag = "24")]
+    pub params: ::core::option::Option<ComputeParams>,
+    /// jobs with a higher priority are queued before jobs with a lower priority by the priority scheduler
+    #[prost(uint32, tag = "25")]
+    pub priority: u32,
+    /// 1-based position of a queued job in the jobs queue. 0 when the job isn't queued
+    #[prost(uint32, tag = "26")]
+    pub queue_position: u32,
+    /// providers which may compute the job. Any provider when unset
+    #[prost(message, optional, tag = "27")]
+    pub affinity: ::core::option::Option<ProviderAffinity>,
+    /// the job's failed compute cycles retries and failovers in the order they happened
+    #[prost(message, repeated, tag = "28")]
+    pub retries: ::prost::alloc::vec::Vec<ComputeRetry>,
+    /// dir of the job's pos data files. Set when the job is submitted and never changed by config updates
+    #[prost(string, tag = "29")]
+    pub data_dir: ::prost::alloc::string::String,
+    /// max size in bytes of each of the job's data files. 0 for a single data file. Set with data_dir when the job is submitted and never changed by config updates
+    #[prost(uint64, tag = "30")]
+    pub max_file_size: u64,
 }
 /// Nested message and enum types in `Job`.
 pub mod job {
@@ -137,6 +303,75 @@ pub mod job {
         /// Job completed
         Completed = 3,
     }
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Kind {
+        /// compute the pos data and optionally a pow solution
+        Data = 0,
+        /// only search for a pow solution in the job's index range. No pos data is written
+        Pow = 1,
+    }
+}
+/// A retry of a job's failed compute cycle or a failover of a stripe to another provider
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ComputeRetry {
+    #[prost(enumeration = "compute_retry::Action", tag = "1")]
+    pub action: i32,
+    /// time of the retry
+    #[prost(uint64, tag = "2")]
+    pub time: u64,
+    /// index of the stripe in the job's stripes
+    #[prost(uint32, tag = "3")]
+    pub stripe: u32,
+    /// the provider which failed
+    #[prost(uint32, tag = "4")]
+    pub provider_id: u32,
+    /// first index of the failed cycle
+    #[prost(uint64, tag = "5")]
+    pub start_index: u64,
+    /// last index of the failed cycle (inclusive)
+    #[prost(uint64, tag = "6")]
+    pub end_index: u64,
+    /// 1-based retry attempt of a failed cycle. 0 for a failover
+    #[prost(uint32, tag = "7")]
+    pub attempt: u32,
+    /// the provider which continues the stripe after a failover
+    #[prost(uint32, tag = "8")]
+    pub failover_provider_id: u32,
+    /// the cycle's error
+    #[prost(message, optional, tag = "9")]
+    pub error: ::core::option::Option<JobError>,
+}
+/// Nested message and enum types in `ComputeRetry`.
+pub mod compute_retry {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Action {
+        /// the failed cycle was computed again by its provider
+        Retry = 0,
+        /// the stripe's remaining indexes were moved to another provider
+        Failover = 1,
+    }
+}
+/// A contiguous range of a job's indexes which is computed by one compute provider.
+/// Each stripe writes its own region of the job's data files.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct JobStripe {
+    /// first index of the stripe
+    #[prost(uint64, tag = "1")]
+    pub start_index: u64,
+    /// last index of the stripe (inclusive)
+    #[prost(uint64, tag = "2")]
+    pub end_index: u64,
+    /// next index to compute. end_index + 1 when the stripe is completed
+    #[prost(uint64, tag = "3")]
+    pub next_index: u64,
+    /// compute provider processor id which computes the stripe
+    #[prost(uint32, tag = "4")]
+    pub compute_provider_id: u32,
+    /// error if the stripe's computation failed or empty otherwise
+    #[prost(message, optional, tag = "5")]
+    pub last_error: ::core::option::Option<JobError>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct JobError {
@@ -151,8 +386,22 @@ pub mod job_error {
     #[repr(i32)]
     pub enum Error {
         Unknown = 0,
+        /// reading or writing the job's data, checkpoint or metadata files failed
         IoError = 1,
+        /// the compute lib failed a compute cycle or computed fewer hashes than requested
         GpuComputeError = 2,
+        /// job was aborted by a client
+        Canceled = 3,
+        /// job was in progress when the service stopped
+        Interrupted = 4,
+        /// the pow search was exhausted - no pow solution was found within the config's pow search limits
+        PowSearchLimit = 5,
+        /// no space is left for the job's data files
+        DiskFull = 6,
+        /// the compute lib doesn't have the job's compute provider
+        InvalidProvider = 7,
+        /// the job or its existing data files are invalid
+        Validation = 8,
     }
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -171,6 +420,21 @@ pub struct AbortJobRequest {
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AbortJobResponse {}
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MoveJobRequest {
+    /// id of a queued job
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    /// 1-based queue position. A job is moved to the end of the queue when position is after the last job
+    #[prost(uint32, tag = "2")]
+    pub position: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MoveJobResponse {
+    /// queued jobs in queue order
+    #[prost(message, repeated, tag = "1")]
+    pub jobs: ::prost::alloc::vec::Vec<Job>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetAllJobsStatusRequest {}
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetAllJobsStatusResponse {
@@ -190,24 +454,47 @@ pub struct GetJobStatusResponse {
 /// A client request to schedule a job for execution on the server
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AddJobRequest {
-    /// Unique client id (input to pos algo)
+    /// Unique client id (input to pos algo), up to 32 bytes. Shorter ids are padded with zeros
     #[prost(bytes = "vec", tag = "1")]
     pub client_id: ::prost::alloc::vec::Vec<u8>,
     /// Requested pos size in bits
     #[prost(uint64, tag = "2")]
     pub post_size_bits: u64,
-    /// Optional start index - used to continue a stopped job
+    /// Optional start index - used to continue a stopped job. The job's data files must have exactly start_index indexes. A stopped job continues from its written indexes (bits_written / bits_per_index)
     #[prost(uint64, tag = "3")]
     pub start_index: u64,
     /// A name set by client to identify the job
     #[prost(string, tag = "4")]
     pub friendly_name: ::prost::alloc::string::String,
-    /// Target pow difficulty, 32 bytes
+    /// Target pow difficulty, 32 bytes. Required when computing a pow solution
     #[prost(bytes = "vec", tag = "5")]
     pub pow_difficulty: ::prost::alloc::vec::Vec<u8>,
     /// when false, job will only compute leaves. When true, job will compute leaves and a pow solution
     #[prost(bool, tag = "6")]
     pub compute_pow_solution: bool,
+    /// Id of the stopped job to continue. Required when start_index is set
+    #[prost(uint64, tag = "7")]
+    pub job_id: u64,
+    /// The job's kind. A POW job searches for a pow solution in the indexes range [start_index, post_size_bits / bits_per_index)
+    /// of existing pos data. job_id is not used by POW jobs.
+    #[prost(enumeration = "job::Kind", tag = "8")]
+    pub kind: i32,
+    /// number of pow solutions to find. 0 for a single solution
+    #[prost(uint32, tag = "9")]
+    pub pow_solutions_count: u32,
+    /// find all the pow solutions in the job's indexes range. pow_solutions_count is ignored
+    #[prost(bool, tag = "10")]
+    pub all_pow_solutions: bool,
+    /// Optional compute params overrides. Unset params use the server config, or the params of the stopped job when
+    /// continuing a job.
+    #[prost(message, optional, tag = "11")]
+    pub params: ::core::option::Option<ComputeParams>,
+    /// job priority. Used by the priority scheduler and ignored by the fifo scheduler
+    #[prost(uint32, tag = "12")]
+    pub priority: u32,
+    /// Optional providers affinity. A job with an affinity no provider can satisfy is rejected
+    #[prost(message, optional, tag = "13")]
+    pub affinity: ::core::option::Option<ProviderAffinity>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AddJobResponse {
@@ -262,11 +549,9 @@ pub mod pos_data_service_client {
             self.inner.unary(request.into_request(), path, codec).await
         }
         #[doc = " Set service configuration"]
-        #[doc = " Important: Don't set the config while there are jobs running or queued to run."]
+        #[doc = " The config is rejected with FAILED_PRECONDITION while there are jobs running or queued to run unless the"]
+        #[doc = " request's force flag is set, and with INVALID_ARGUMENT when it is invalid or its data dir isn't writable."]
         #[doc = " Config before starting jobs or wait until all jobs have stopped before changing the config."]
-        #[doc = " todo: support removing one or more supported system compute providers from the providers pool."]
-        #[doc = " for example, a laptop may have a supported Intel internal gpu which is very slow and user"]
-        #[doc = " might want to use the 2nd laptop integral AMD/Nvidia gpu which has strong performance."]
         pub async fn set_config(
             &mut self,
             request: impl tonic::IntoRequest<super::SetConfigRequest>,
@@ -281,6 +566,37 @@ pub mod pos_data_service_client {
             let path = http::uri::PathAndQuery::from_static("/api.PosDataService/SetConfig");
             self.inner.unary(request.into_request(), path, codec).await
         }
+        #[doc = " Add the providers matching a filter to the providers pool. Quarantined providers are released from quarantine"]
+        pub async fn enable_provider(
+            &mut self,
+            request: impl tonic::IntoRequest<super::EnableProviderRequest>,
+        ) -> Result<tonic::Response<super::EnableProviderResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/api.PosDataService/EnableProvider");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        #[doc = " Remove the providers matching a filter from the providers pool. For example, a laptop may have a"]
+        #[doc = " very slow internal Intel gpu and a strong AMD/Nvidia gpu. A busy provider is removed once its job stops."]
+        pub async fn disable_provider(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DisableProviderRequest>,
+        ) -> Result<tonic::Response<super::DisableProviderResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/api.PosDataService/DisableProvider");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
         #[doc = " Get service current configuration"]
         pub async fn get_config(
             &mut self,
@@ -342,7 +658,9 @@ pub mod pos_data_service_client {
                 http::uri::PathAndQuery::from_static("/api.PosDataService/GetAllJobsStatuses");
             self.inner.unary(request.into_request(), path, codec).await
         }
-        #[doc = " Abort ajJob or abort all Jobs and optionally delete them"]
+        #[doc = " Abort a Job or abort all Jobs and optionally delete them."]
+        #[doc = " An in-progress job is stopped at the end of its current compute cycle and its provider is released"]
+        #[doc = " to the providers pool. Aborted jobs are stopped with a CANCELED job error."]
         pub async fn abort_job(
             &mut self,
             request: impl tonic::IntoRequest<super::AbortJobRequest>,
@@ -357,6 +675,22 @@ pub mod pos_data_service_client {
             let path = http::uri::PathAndQuery::from_static("/api.PosDataService/AbortJob");
             self.inner.unary(request.into_request(), path, codec).await
         }
+        #[doc = " Move a queued job to a position in the jobs queue. Queued jobs are started in queue order"]
+        #[doc = " when providers which may compute them are idle."]
+        pub async fn move_job(
+            &mut self,
+            request: impl tonic::IntoRequest<super::MoveJobRequest>,
+        ) -> Result<tonic::Response<super::MoveJobResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/api.PosDataService/MoveJob");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
         #[doc = " Subscribe to stream of job status updates for a specific job or for all jobs"]
         pub async fn subscribe_job_status_stream(
             &mut self,
@@ -405,15 +739,24 @@ pub mod pos_data_service_server {
             request: tonic::Request<super::GetProvidersRequest>,
         ) -> Result<tonic::Response<super::GetProvidersResponse>, tonic::Status>;
         #[doc = " Set service configuration"]
-        #[doc = " Important: Don't set the config while there are jobs running or queued to run."]
+        #[doc = " The config is rejected with FAILED_PRECONDITION while there are jobs running or queued to run unless the"]
+        #[doc = " request's force flag is set, and with INVALID_ARGUMENT when it is invalid or its data dir isn't writable."]
         #[doc = " Config before starting jobs or wait until all jobs have stopped before changing the config."]
-        #[doc = " todo: support removing one or more supported system compute providers from the providers pool."]
-        #[doc = " for example, a laptop may have a supported Intel internal gpu which is very slow and user"]
-        #[doc = " might want to use the 2nd laptop integral AMD/Nvidia gpu which has strong performance."]
         async fn set_config(
             &self,
             request: tonic::Request<super::SetConfigRequest>,
         ) -> Result<tonic::Response<super::SetConfigResponse>, tonic::Status>;
+        #[doc = " Add the providers matching a filter to the providers pool. Quarantined providers are released from quarantine"]
+        async fn enable_provider(
+            &self,
+            request: tonic::Request<super::EnableProviderRequest>,
+        ) -> Result<tonic::Response<super::EnableProviderResponse>, tonic::Status>;
+        #[doc = " Remove the providers matching a filter from the providers pool. For example, a laptop may have a"]
+        #[doc = " very slow internal Intel gpu and a strong AMD/Nvidia gpu. A busy provider is removed once its job stops."]
+        async fn disable_provider(
+            &self,
+            request: tonic::Request<super::DisableProviderRequest>,
+        ) -> Result<tonic::Response<super::DisableProviderResponse>, tonic::Status>;
         #[doc = " Get service current configuration"]
         async fn get_config(
             &self,
@@ -434,11 +777,19 @@ pub mod pos_data_service_server {
             &self,
             request: tonic::Request<super::GetAllJobsStatusRequest>,
         ) -> Result<tonic::Response<super::GetAllJobsStatusResponse>, tonic::Status>;
-        #[doc = " Abort ajJob or abort all Jobs and optionally delete them"]
+        #[doc = " Abort a Job or abort all Jobs and optionally delete them."]
+        #[doc = " An in-progress job is stopped at the end of its current compute cycle and its provider is released"]
+        #[doc = " to the providers pool. Aborted jobs are stopped with a CANCELED job error."]
         async fn abort_job(
             &self,
             request: tonic::Request<super::AbortJobRequest>,
         ) -> Result<tonic::Response<super::AbortJobResponse>, tonic::Status>;
+        #[doc = " Move a queued job to a position in the jobs queue. Queued jobs are started in queue order"]
+        #[doc = " when providers which may compute them are idle."]
+        async fn move_job(
+            &self,
+            request: tonic::Request<super::MoveJobRequest>,
+        ) -> Result<tonic::Response<super::MoveJobResponse>, tonic::Status>;
         #[doc = "Server streaming response type for the SubscribeJobStatusStream method."]
         type SubscribeJobStatusStreamStream: futures_core::Stream<Item = Result<super::JobStatusStreamResponse, tonic::Status>>
             + Send
@@ -546,6 +897,74 @@ pub mod pos_data_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/api.PosDataService/EnableProvider" => {
+                    #[allow(non_camel_case_types)]
+                    struct EnableProviderSvc<T: PosDataService>(pub Arc<T>);
+                    impl<T: PosDataService>
+                        tonic::server::UnaryService<super::EnableProviderRequest>
+                        for EnableProviderSvc<T>
+                    {
+                        type Response = super::EnableProviderResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::EnableProviderRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).enable_provider(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = EnableProviderSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/api.PosDataService/DisableProvider" => {
+                    #[allow(non_camel_case_types)]
+                    struct DisableProviderSvc<T: PosDataService>(pub Arc<T>);
+                    impl<T: PosDataService>
+                        tonic::server::UnaryService<super::DisableProviderRequest>
+                        for DisableProviderSvc<T>
+                    {
+                        type Response = super::DisableProviderResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DisableProviderRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).disable_provider(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = DisableProviderSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/api.PosDataService/GetConfig" => {
                     #[allow(non_camel_case_types)]
                     struct GetConfigSvc<T: PosDataService>(pub Arc<T>);
@@ -706,6 +1125,37 @@ pub mod pos_data_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/api.PosDataService/MoveJob" => {
+                    #[allow(non_camel_case_types)]
+                    struct MoveJobSvc<T: PosDataService>(pub Arc<T>);
+                    impl<T: PosDataService> tonic::server::UnaryService<super::MoveJobRequest> for MoveJobSvc<T> {
+                        type Response = super::MoveJobResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::MoveJobRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).move_job(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let interceptor = inner.1.clone();
+                        let inner = inner.0;
+                        let method = MoveJobSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = if let Some(interceptor) = interceptor {
+                            tonic::server::Grpc::with_interceptor(codec, interceptor)
+                        } else {
+                            tonic::server::Grpc::new(codec)
+                        };
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/api.PosDataService/SubscribeJobStatusStream" => {
                     #[allow(non_camel_case_types)]
                     struct SubscribeJobStatusStreamSvc<T: PosDataService>(pub Arc<T>);
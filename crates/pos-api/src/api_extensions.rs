@@ -1,10 +1,16 @@
-use crate::api::job::JobStatus;
-use crate::api::{Job, Provider};
+use crate::api::job::{JobStatus, Kind};
+use crate::api::job_error::Error;
+use crate::api::provider_affinity::Affinity;
+use crate::api::provider_filter::Filter;
+use crate::api::{
+    ComputeParams, Config, Job, JobError, JobStripe, Provider, ProviderAffinity, ProviderFilter,
+};
 use anyhow::{bail, Result};
 use chrono::{DateTime, Local, TimeZone};
 use std::convert::TryFrom;
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use std::io;
 use std::ops::Sub;
 
 // Compute operation result
@@ -17,6 +23,7 @@ pub enum ComputeResults {
     Canceled = -4,
     MissingComputeOptions = -5,
     InvalidParam = -6,
+    InvalidProviderId = -7,
 }
 
 impl TryFrom<i32> for ComputeResults {
@@ -35,6 +42,9 @@ impl TryFrom<i32> for ComputeResults {
                 Ok(ComputeResults::MissingComputeOptions)
             }
             x if x == ComputeResults::InvalidParam as i32 => Ok(ComputeResults::InvalidParam),
+            x if x == ComputeResults::InvalidProviderId as i32 => {
+                Ok(ComputeResults::InvalidProviderId)
+            }
 
             _ => Err(()),
         }
@@ -52,11 +62,88 @@ impl Display for ComputeResults {
             ComputeResults::Canceled => "Canceled",
             ComputeResults::MissingComputeOptions => "Missing options",
             ComputeResults::InvalidParam => "Invalid params",
+            ComputeResults::InvalidProviderId => "Invalid provider id",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// Job error of a compute lib result
+impl From<ComputeResults> for Error {
+    fn from(res: ComputeResults) -> Self {
+        match res {
+            ComputeResults::NoError | ComputeResults::PowSolutionFound => Error::Unknown,
+            ComputeResults::Canceled => Error::Canceled,
+            ComputeResults::InvalidProviderId => Error::InvalidProvider,
+            ComputeResults::ComputeError
+            | ComputeResults::Timeout
+            | ComputeResults::Already
+            | ComputeResults::MissingComputeOptions
+            | ComputeResults::InvalidParam => Error::GpuComputeError,
+        }
+    }
+}
+
+/// Job error of an io error. Checks the os error code as io error kinds of a full disk aren't stable.
+impl From<&io::Error> for Error {
+    fn from(e: &io::Error) -> Self {
+        #[cfg(unix)]
+        let disk_full = matches!(e.raw_os_error(), Some(libc::ENOSPC) | Some(libc::EDQUOT));
+        // ERROR_HANDLE_DISK_FULL and ERROR_DISK_FULL
+        #[cfg(windows)]
+        let disk_full = matches!(e.raw_os_error(), Some(39) | Some(112));
+        #[cfg(not(any(unix, windows)))]
+        let disk_full = false;
+
+        match disk_full {
+            true => Error::DiskFull,
+            false => Error::IoError,
+        }
+    }
+}
+
+impl Error {
+    /// Returns the job error of an error. The error of an io error which caused it, or default otherwise.
+    pub fn from_error(e: &anyhow::Error, default: Error) -> Error {
+        e.chain()
+            .find_map(|cause| cause.downcast_ref::<io::Error>())
+            .map_or(default, Error::from)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let str = match self {
+            Error::Unknown => "unknown error",
+            Error::IoError => "io error",
+            Error::GpuComputeError => "gpu compute error",
+            Error::Canceled => "canceled",
+            Error::Interrupted => "interrupted",
+            Error::PowSearchLimit => "pow search limit",
+            Error::DiskFull => "disk full",
+            Error::InvalidProvider => "invalid provider",
+            Error::Validation => "validation error",
         };
         write!(f, "{}", str)
     }
 }
 
+impl JobError {
+    /// Returns a job error of a compute lib result code
+    pub fn compute(res: i32, message: &str) -> JobError {
+        match ComputeResults::try_from(res) {
+            Ok(result) => JobError {
+                message: format!("{}: {}", message, result),
+                error: Error::from(result) as i32,
+            },
+            Err(()) => JobError {
+                error: Error::GpuComputeError as i32,
+                message: format!("{}: unknown compute result {}", message, res),
+            },
+        }
+    }
+}
+
 pub enum ComputeOptions {
     ComputeLeaves = 1,
     ComputePow = 2,
@@ -77,6 +164,92 @@ pub fn get_provider_class_string(class: u32) -> &'static str {
     }
 }
 
+impl ProviderFilter {
+    /// Parse a provider filter from a config string: id:<id>, model:<pattern> or class:<class>
+    pub fn parse(filter: &str) -> Result<ProviderFilter> {
+        let (key, value) = match filter.trim().split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => bail!(
+                "invalid provider filter {}. Expected id:<id>, model:<pattern> or class:<class>",
+                filter
+            ),
+        };
+
+        let filter = match key {
+            "id" => Filter::Id(value.parse()?),
+            "model" => Filter::Model(value.to_string()),
+            "class" => Filter::Class(value.to_string()),
+            _ => bail!("unknown provider filter key {} in {}", key, filter),
+        };
+        Ok(ProviderFilter {
+            filter: Some(filter),
+        })
+    }
+
+    /// Returns true when the filter selects the provider. An empty filter selects no provider.
+    pub fn matches(&self, provider: &Provider) -> bool {
+        match self.filter.as_ref() {
+            Some(Filter::Id(id)) => provider.id == *id,
+            Some(Filter::Model(pattern)) => {
+                matches_pattern(&pattern.to_lowercase(), &provider.model.to_lowercase())
+            }
+            Some(Filter::Class(class)) => {
+                get_provider_class_string(provider.class as u32).eq_ignore_ascii_case(class)
+            }
+            None => false,
+        }
+    }
+}
+
+impl Display for ProviderFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.filter.as_ref() {
+            Some(Filter::Id(id)) => write!(f, "id:{}", id),
+            Some(Filter::Model(pattern)) => write!(f, "model:{}", pattern),
+            Some(Filter::Class(class)) => write!(f, "class:{}", class),
+            None => write!(f, "empty filter"),
+        }
+    }
+}
+
+impl ProviderAffinity {
+    /// Returns true when a job with this affinity may be computed by the provider. An empty affinity matches any provider.
+    pub fn matches(&self, provider: &Provider) -> bool {
+        match self.affinity.as_ref() {
+            Some(Affinity::ProviderId(id)) => provider.id == *id,
+            Some(Affinity::Class(class)) => provider.class == *class,
+            Some(Affinity::MinHashesPerSec(rate)) => provider.hashes_per_sec >= *rate,
+            None => true,
+        }
+    }
+}
+
+impl Display for ProviderAffinity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.affinity.as_ref() {
+            Some(Affinity::ProviderId(id)) => write!(f, "provider {}", id),
+            Some(Affinity::Class(class)) => {
+                write!(f, "class {}", get_provider_class_string(*class as u32))
+            }
+            Some(Affinity::MinHashesPerSec(rate)) => write!(f, "at least {} h/s", rate),
+            None => write!(f, "any provider"),
+        }
+    }
+}
+
+/// Returns true when text matches a pattern where * matches any text
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((prefix, rest)) => {
+            text.starts_with(prefix)
+                && (0..=text.len() - prefix.len())
+                    .filter(|i| text.is_char_boundary(prefix.len() + i))
+                    .any(|i| matches_pattern(rest, &text[prefix.len() + i..]))
+        }
+    }
+}
+
 impl TryFrom<i32> for JobStatus {
     type Error = ();
     fn try_from(v: i32) -> Result<Self, Self::Error> {
@@ -107,12 +280,89 @@ impl Job {
         format!("{}.pos", self.id)
     }
 
+    /// Returns the name of the job's nth pos data file when its data is split into files in the pos data dir
+    pub fn data_file_name(&self, file_index: u64) -> String {
+        format!("{}_{}.pos", self.id, file_index)
+    }
+
+    /// Returns the number of indexes computed by all of the job's stripes
+    pub fn indexes_written(&self) -> u64 {
+        self.stripes.iter().map(|s| s.indexes_written()).sum()
+    }
+
+    /// Returns true when the job only searches for a pow solution and has no pos data
+    pub fn is_pow_only(&self) -> bool {
+        self.kind == Kind::Pow as i32
+    }
+
+    /// Returns the number of pow solutions the job finds. usize::MAX when it finds all the solutions in its range
+    pub fn pow_solutions_wanted(&self) -> usize {
+        match self.all_pow_solutions {
+            true => usize::MAX,
+            false => self.pow_solutions_count.max(1) as usize,
+        }
+    }
+
+    /// Returns true when a pow solution at index or after it may be one of the job's wanted solutions
+    pub fn needs_pow_solution_at(&self, index: u64) -> bool {
+        if !self.compute_pow_solution {
+            return false;
+        }
+        match self
+            .pow_solutions
+            .get(self.pow_solutions_wanted().saturating_sub(1))
+        {
+            Some(last) => index < *last,
+            None => true,
+        }
+    }
+
+    /// Add found pow solutions. Only the job's first wanted solutions are kept in index order.
+    pub fn add_pow_solutions(&mut self, solutions: &[u64]) {
+        self.pow_solutions.extend_from_slice(solutions);
+        self.pow_solutions.sort_unstable();
+        self.pow_solutions.dedup();
+        self.pow_solutions.truncate(self.pow_solutions_wanted());
+        self.pow_solution_index = self.pow_solutions.first().copied().unwrap_or(u64::MAX);
+    }
+
+    /// Returns the name of the job's metadata file in the pos data dir
+    pub fn metadata_file_name(&self) -> String {
+        format!("{}.data.json", self.id)
+    }
+
     /// Validate job data
     pub fn validate(&self, index_per_compute: u64, label_size: u32) -> Result<()> {
         if label_size != 8 {
             // we only support 8 bit labels
             bail!("only 8 bits label size is supported")
         }
+        if Kind::from_i32(self.kind).is_none() {
+            bail!("unknown job kind {}", self.kind)
+        }
+
+        if self.client_id.len() > 32 {
+            bail!(
+                "client id must be at most 32 bytes. Got {} bytes",
+                self.client_id.len()
+            )
+        }
+
+        if (self.compute_pow_solution || !self.pow_difficulty.is_empty())
+            && self.pow_difficulty.len() != 32
+        {
+            bail!(
+                "pow difficulty must be 32 bytes. Got {} bytes",
+                self.pow_difficulty.len()
+            )
+        }
+
+        if let Some(params) = self.params.as_ref() {
+            if !params.salt.is_empty() && params.salt.len() != 32 {
+                bail!("salt must be 32 bytes. Got {} bytes", params.salt.len())
+            }
+        }
+
         let min_size = index_per_compute * label_size as u64;
         if self.size_bits < min_size {
             bail!(
@@ -122,15 +372,123 @@ impl Job {
             )
         }
 
-        let n: f32 = self.size_bits as f32 / 8.0;
-        if (n - n.round()).abs() > 0.000001 {
-            bail!("only sizes which are multiples of bytes are supported");
+        if self.size_bits % 8 != 0 {
+            bail!(
+                "only sizes which are multiples of bytes are supported. Requested {} bits",
+                self.size_bits
+            );
+        }
+
+        if self.bits_written > self.size_bits {
+            bail!(
+                "start index is out of range. Data written (bits): {}. Requested {}",
+                self.bits_written,
+                self.size_bits
+            )
         }
 
         Ok(())
     }
 }
 
+impl ComputeParams {
+    /// Returns the params with unset params taken from defaults
+    pub fn or(&self, defaults: &ComputeParams) -> ComputeParams {
+        ComputeParams {
+            salt: match self.salt.is_empty() {
+                true => defaults.salt.clone(),
+                false => self.salt.clone(),
+            },
+            n: if self.n == 0 { defaults.n } else { self.n },
+            r: if self.r == 0 { defaults.r } else { self.r },
+            p: if self.p == 0 { defaults.p } else { self.p },
+            bits_per_index: if self.bits_per_index == 0 {
+                defaults.bits_per_index
+            } else {
+                self.bits_per_index
+            },
+            indexes_per_compute_cycle: if self.indexes_per_compute_cycle == 0 {
+                defaults.indexes_per_compute_cycle
+            } else {
+                self.indexes_per_compute_cycle
+            },
+        }
+    }
+}
+
+impl From<&Config> for ComputeParams {
+    fn from(config: &Config) -> Self {
+        ComputeParams {
+            salt: config.salt.clone(),
+            n: config.n,
+            r: config.r,
+            p: config.p,
+            bits_per_index: config.bits_per_index,
+            indexes_per_compute_cycle: config.indexes_per_compute_cycle,
+        }
+    }
+}
+
+impl Config {
+    /// Validate the config's compute params
+    pub fn validate(&self) -> Result<()> {
+        if self.salt.len() != 32 {
+            bail!("salt must be 32 bytes. Got {} bytes", self.salt.len())
+        }
+
+        if !self.n.is_power_of_two() {
+            bail!("N must be a power of two. Got {}", self.n)
+        }
+
+        if self.r == 0 || self.p == 0 {
+            bail!(
+                "r and p must be positive. Got r {} and p {}",
+                self.r,
+                self.p
+            )
+        }
+
+        if self.bits_per_index != 8 {
+            // we only support 8 bit labels
+            bail!(
+                "only 8 bits per index are supported. Got {}",
+                self.bits_per_index
+            )
+        }
+
+        if self.indexes_per_compute_cycle == 0 {
+            bail!("indexes per compute cycle must be positive")
+        }
+
+        Ok(())
+    }
+
+    /// Returns the config with its compute params replaced by params
+    pub fn with_params(&self, params: &ComputeParams) -> Config {
+        Config {
+            salt: params.salt.clone(),
+            n: params.n,
+            r: params.r,
+            p: params.p,
+            bits_per_index: params.bits_per_index,
+            indexes_per_compute_cycle: params.indexes_per_compute_cycle,
+            ..self.clone()
+        }
+    }
+}
+
+impl JobStripe {
+    /// Returns true when all of the stripe's indexes were computed
+    pub fn is_completed(&self) -> bool {
+        self.next_index > self.end_index
+    }
+
+    /// Returns the number of the stripe's computed indexes
+    pub fn indexes_written(&self) -> u64 {
+        self.next_index - self.start_index
+    }
+}
+
 impl Display for Provider {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "id: {}. ", self.id)?;
@@ -146,6 +504,12 @@ impl Display for Job {
         write!(f, "size (bits): {}. ", self.size_bits)?;
         let status = JobStatus::try_from(self.status).unwrap();
         write!(f, "status: {}. ", status)?;
+        if self.queue_position != 0 {
+            write!(f, "queue position: {}. ", self.queue_position)?;
+        }
+        if self.priority != 0 {
+            write!(f, "priority: {}. ", self.priority)?;
+        }
 
         let submitted: DateTime<Local> = Local.timestamp(self.submitted as i64, 0);
         write!(f, "submitted: {}. ", submitted.to_rfc2822())?;
@@ -176,16 +540,49 @@ impl Display for Job {
         write!(f, "data written (bits): {}. ", self.bits_written)?;
         write!(f, "client id: {}. ", hex::encode(self.client_id.clone()))?;
         if let Some(err) = self.last_error.as_ref() {
-            write!(f, "last Error: {}, {}. ", err.error, err.message)?;
+            match Error::from_i32(err.error) {
+                Some(error) => write!(f, "last Error: {}, {}. ", error, err.message)?,
+                None => write!(f, "last Error: {}, {}. ", err.error, err.message)?,
+            }
         }
 
         write!(f, "gpu id: {}. ", self.compute_provider_id)?;
+        if self.is_pow_only() {
+            write!(
+                f,
+                "pow only. hashes searched: {}. ",
+                self.pow_hashes_searched
+            )?;
+        }
+        if self.stripes.len() > 1 {
+            let completed = self.stripes.iter().filter(|s| s.is_completed()).count();
+            write!(
+                f,
+                "stripes: {} ({} completed). ",
+                self.stripes.len(),
+                completed
+            )?;
+        }
+
+        if !self.retries.is_empty() {
+            write!(f, "compute retries: {}. ", self.retries.len())?;
+        }
 
         if !self.compute_pow_solution {
             write!(f, "pow compute is off.")
         } else {
             if self.pow_solution_index == u64::MAX {
                 write!(f, " 🧱 pow solution not found. ")?;
+                if self.pow_search_index != 0 {
+                    write!(f, "pow search index: {}. ", self.pow_search_index)?;
+                }
+            } else if self.pow_solutions.len() > 1 {
+                write!(
+                    f,
+                    " 🧱👊 pow solutions: {}. first solution index: {}. ",
+                    self.pow_solutions.len(),
+                    self.pow_solution_index
+                )?;
             } else {
                 write!(f, " 🧱👊 pow solution index: {}. ", self.pow_solution_index)?;
             }
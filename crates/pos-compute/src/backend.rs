@@ -0,0 +1,133 @@
+use crate::{
+    ComputeError, ComputeOutcome, ComputeRequest, GpuSetup, LibraryError, PosComputeProvider,
+    LABEL_SIZE, OPTIONS,
+};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A compute backend computes pos hashes with its compute providers.
+/// The backend is shared by the blocking tasks which compute jobs so it must be thread safe.
+pub trait ComputeBackend: Send + Sync {
+    /// Returns the backend's compute providers
+    fn providers(&self) -> Vec<PosComputeProvider>;
+
+    /// Compute the hashes of a request's positions to the start of out.
+    /// out must be at least the request's out_size bytes
+    fn compute(
+        &self,
+        request: &ComputeRequest,
+        out: &mut [u8],
+    ) -> Result<ComputeOutcome, ComputeError>;
+
+    /// Stop all in-progress computations. Stopped computations return a canceled error
+    fn stop(&self, ms_timeout: u32) -> Result<(), ComputeError>;
+
+    /// Returns true while a stop is in progress
+    fn stop_in_progress(&self) -> bool;
+
+    /// Stop all work of the backend's providers before the process exits
+    fn shutdown(&self) -> Result<(), ComputeError>;
+
+    /// Enable or disable the backend's log output
+    fn set_logging(&self, _enable: bool) {}
+
+    /// Validate a provider's computations with the backend's self tests
+    fn self_test(&self, _provider_id: u32) -> Result<(), ComputeError> {
+        Ok(())
+    }
+
+    /// Benchmark a provider by computing labels_count labels with the default scrypt params.
+    /// Returns the provider's hash rate or None when the computation failed.
+    fn benchmark(&self, provider_id: u32, labels_count: u64) -> Option<u64> {
+        let request = ComputeRequest {
+            provider_id,
+            end_position: labels_count - 1,
+            hash_len_bits: LABEL_SIZE,
+            options: OPTIONS::ComputeLeaves as u32,
+            n: 512,
+            r: 1,
+            p: 1,
+            ..Default::default()
+        };
+        let mut out = vec![0_u8; request.out_size()];
+        self.compute(&request, &mut out)
+            .ok()
+            .map(|outcome| outcome.hashes_per_sec)
+    }
+}
+
+/// A computation which is counted in its backend's in-progress computations until it's dropped,
+/// including when it ends with a panic, so stops never wait for it after it ended
+pub(crate) struct InProgress<'a>(&'a AtomicUsize);
+
+impl<'a> InProgress<'a> {
+    pub(crate) fn start(in_progress: &'a AtomicUsize) -> Self {
+        in_progress.fetch_add(1, Ordering::SeqCst);
+        InProgress(in_progress)
+    }
+}
+
+impl Drop for InProgress<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// The gpu-setup compute lib backend
+#[derive(Clone, Copy)]
+pub struct FfiBackend {
+    lib: &'static GpuSetup,
+}
+
+impl FfiBackend {
+    /// Create a backend of a loaded lib
+    pub fn new(lib: &'static GpuSetup) -> Self {
+        FfiBackend { lib }
+    }
+
+    /// Load the lib from a path, or from its default path when path is None, and create a backend of it
+    pub fn load(path: Option<&Path>) -> Result<Self, LibraryError> {
+        Ok(FfiBackend::new(GpuSetup::load(path)?))
+    }
+
+    /// Returns the backend's lib
+    pub fn lib(&self) -> &'static GpuSetup {
+        self.lib
+    }
+}
+
+impl ComputeBackend for FfiBackend {
+    fn providers(&self) -> Vec<PosComputeProvider> {
+        self.lib.providers()
+    }
+
+    fn compute(
+        &self,
+        request: &ComputeRequest,
+        out: &mut [u8],
+    ) -> Result<ComputeOutcome, ComputeError> {
+        self.lib.compute(request, out)
+    }
+
+    fn stop(&self, ms_timeout: u32) -> Result<(), ComputeError> {
+        self.lib.stop(ms_timeout)
+    }
+
+    fn stop_in_progress(&self) -> bool {
+        self.lib.stop_in_progress()
+    }
+
+    fn shutdown(&self) -> Result<(), ComputeError> {
+        let res = self.stop(0);
+        self.lib.shutdown();
+        res
+    }
+
+    fn set_logging(&self, enable: bool) {
+        self.lib.set_logging(enable)
+    }
+
+    fn self_test(&self, provider_id: u32) -> Result<(), ComputeError> {
+        self.lib.self_test(provider_id)
+    }
+}
@@ -0,0 +1,498 @@
+use crate::{ComputeError, ComputeOutcome, ComputeRequest, PosComputeProvider};
+use crate::{HASH_SIZE, SPACEMESH_API_POW_SOLUTION_FOUND};
+use std::env;
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::ffi::c_void;
+use std::fmt;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::Mutex;
+
+/// Env var of the gpu-setup lib path. It is used when the lib is loaded without a path
+pub const GPU_SETUP_LIB_ENV: &str = "GPU_SETUP_LIB";
+
+/// Size in bytes of the unit test hash input
+pub const UNIT_TEST_INPUT_SIZE: usize = 80;
+
+/// Number of hashes computed by the unit test hash
+pub const UNIT_TEST_HASHES: usize = 128;
+
+/// The unit test hashes of a zeros input. These are the test vector hashes of the gpu-setup-test tool
+const UNIT_TEST_HASHES_RESULT: &[u8; UNIT_TEST_HASHES * HASH_SIZE] =
+    include_bytes!("../resources/unit_test_hashes.bin");
+
+type ScryptPositionsFn = unsafe extern "C" fn(
+    provider_id: u32,       // POST compute provider ID
+    id: *const u8,          // 32 bytes
+    start_position: u64,    // e.g. 0
+    end_position: u64,      // e.g. 49,999
+    hash_len_bits: u32, // (1...256) for each hash output, the number of prefix bits (not bytes) to copy into the buffer
+    salt: *const u8,    // 32 bytes
+    options: u32,       // throttle, leafs, pow etc.
+    out: *mut u8, // memory buffer large enough to include hash_len_bits * number of requested hashes
+    n: u32,       // scrypt N
+    r: u32,       // scrypt r
+    p: u32,       // scrypt p
+    d: *const u8, // Target D for the POW computation. 32 bytes.
+    idx_solution: *mut u64, // pow solution index
+    hashes_computed: *mut u64,
+    hashes_per_sec: *mut u64,
+) -> i32;
+
+// stop all GPU work and don't fill the passed-in buffer with any more results.
+type StopFn = unsafe extern "C" fn(ms_timeout: u32) -> i32;
+
+// return non-zero if stop in progress
+type StopInprogressFn = unsafe extern "C" fn() -> i32;
+
+// return POST compute providers info
+type GetProvidersFn = unsafe extern "C" fn(
+    providers: *mut u8, // out providers info buffer, if NULL - return count of available providers
+    max_providers: i32, // buffer size
+) -> i32;
+
+// enable/disable log output
+type LoggingFn = unsafe extern "C" fn(enable: i32);
+
+// library shutdown
+type ShutdownFn = unsafe extern "C" fn();
+
+// compute the unit test hashes of an input. returns the number of computed hashes
+type UnitTestHashFn =
+    unsafe extern "C" fn(provider_id: u32, input: *mut u8, hashes: *mut u8) -> i64;
+
+// write the prefix bits of hashes to a bit stream. returns the number of bytes written to output
+type UnitTestBitStreamFn = unsafe extern "C" fn(
+    provider_id: u32,
+    hashes: *mut u8,
+    count: u64,
+    output: *mut u8,
+    hash_len_bits: u32,
+) -> i64;
+
+/// An error of loading the gpu-setup lib
+#[derive(Debug, Clone)]
+pub enum LibraryError {
+    /// The lib file is missing or it could not be loaded
+    NotLoaded { path: PathBuf, reason: String },
+    /// The lib doesn't export a function of the api. It was built from an incompatible gpu-post version
+    MissingSymbol { path: PathBuf, symbol: &'static str },
+}
+
+impl fmt::Display for LibraryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LibraryError::NotLoaded { path, reason } => write!(
+                f,
+                "failed to load the gpu-setup lib {}: {}",
+                path.display(),
+                reason
+            ),
+            LibraryError::MissingSymbol { path, symbol } => write!(
+                f,
+                "{} is not a compatible gpu-setup lib version: it doesn't export {}",
+                path.display(),
+                symbol
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LibraryError {}
+
+/// A gpu-setup lib which was loaded at runtime. Loaded libs are not unloaded
+pub struct GpuSetup {
+    path: PathBuf,
+    scrypt_positions: ScryptPositionsFn,
+    stop: StopFn,
+    stop_inprogress: StopInprogressFn,
+    get_providers: GetProvidersFn,
+    logging: LoggingFn,
+    shutdown: ShutdownFn,
+    unit_test_hash: UnitTestHashFn,
+    unit_test_bit_stream: UnitTestBitStreamFn,
+}
+
+/// Libs loaded by this process
+static LOADED: Mutex<Vec<&'static GpuSetup>> = Mutex::new(Vec::new());
+
+impl GpuSetup {
+    /// Returns the platform's file name of the lib, e.g. libgpu-setup.so
+    pub fn file_name() -> String {
+        format!("{}gpu-setup{}", DLL_PREFIX, DLL_SUFFIX)
+    }
+
+    /// Returns the lib path which is loaded when a path is not provided: the GPU_SETUP_LIB env var path,
+    /// or the lib file in the executable's dir, or the lib file name which is searched in the platform's
+    /// dynamic libs search path
+    pub fn default_path() -> PathBuf {
+        if let Some(path) = env::var_os(GPU_SETUP_LIB_ENV).filter(|p| !p.is_empty()) {
+            return PathBuf::from(path);
+        }
+        if let Some(dir) = env::current_exe()
+            .ok()
+            .and_then(|p| p.parent().map(Path::to_path_buf))
+        {
+            let path = dir.join(GpuSetup::file_name());
+            if path.is_file() {
+                return path;
+            }
+        }
+        PathBuf::from(GpuSetup::file_name())
+    }
+
+    /// Load the lib from a path or from the default path when path is None.
+    /// A lib is loaded once per process and later loads of its path return it
+    pub fn load(path: Option<&Path>) -> Result<&'static GpuSetup, LibraryError> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => GpuSetup::default_path(),
+        };
+        let mut loaded = LOADED.lock().unwrap();
+        if let Some(lib) = loaded.iter().find(|lib| lib.path == path) {
+            return Ok(lib);
+        }
+
+        let handle = unsafe { os::open(&path) }.map_err(|reason| LibraryError::NotLoaded {
+            path: path.clone(),
+            reason,
+        })?;
+        let symbol = |symbol: &'static str| match unsafe { os::symbol(handle, symbol) } {
+            s if s.is_null() => Err(LibraryError::MissingSymbol {
+                path: path.clone(),
+                symbol,
+            }),
+            s => Ok(s),
+        };
+        let symbols = (|| {
+            Ok((
+                symbol("scryptPositions")?,
+                symbol("stop")?,
+                symbol("spacemesh_api_stop_inprogress")?,
+                symbol("spacemesh_api_get_providers")?,
+                symbol("spacemesh_api_logging")?,
+                symbol("spacemesh_api_shutdown")?,
+                symbol("unit_test_hash")?,
+                symbol("unit_test_bit_stream")?,
+            ))
+        })();
+        let (
+            scrypt_positions,
+            stop,
+            stop_inprogress,
+            get_providers,
+            logging,
+            shutdown,
+            unit_test_hash,
+            unit_test_bit_stream,
+        ) = match symbols {
+            Ok(symbols) => symbols,
+            Err(e) => {
+                unsafe { os::close(handle) };
+                return Err(e);
+            }
+        };
+
+        // the symbols are the api.h functions of the lib
+        let lib = unsafe {
+            GpuSetup {
+                path,
+                scrypt_positions: mem::transmute::<*mut c_void, ScryptPositionsFn>(
+                    scrypt_positions,
+                ),
+                stop: mem::transmute::<*mut c_void, StopFn>(stop),
+                stop_inprogress: mem::transmute::<*mut c_void, StopInprogressFn>(stop_inprogress),
+                get_providers: mem::transmute::<*mut c_void, GetProvidersFn>(get_providers),
+                logging: mem::transmute::<*mut c_void, LoggingFn>(logging),
+                shutdown: mem::transmute::<*mut c_void, ShutdownFn>(shutdown),
+                unit_test_hash: mem::transmute::<*mut c_void, UnitTestHashFn>(unit_test_hash),
+                unit_test_bit_stream: mem::transmute::<*mut c_void, UnitTestBitStreamFn>(
+                    unit_test_bit_stream,
+                ),
+            }
+        };
+        let lib: &'static GpuSetup = Box::leak(Box::new(lib));
+        loaded.push(lib);
+        Ok(lib)
+    }
+
+    /// Returns the path the lib was loaded from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the lib's compute providers
+    pub fn providers(&self) -> Vec<PosComputeProvider> {
+        unsafe {
+            let p: *mut u8 = ptr::null_mut();
+            let providers_count = (self.get_providers)(p, 0);
+            let mut dst: Vec<PosComputeProvider> =
+                Vec::with_capacity(providers_count.max(0) as usize);
+            if providers_count > 0 {
+                let mut buffer: Vec<u8> = Vec::new();
+                buffer.resize((providers_count * 264) as usize, 0);
+                let pdst = buffer.as_mut_ptr();
+                (self.get_providers)(pdst as *mut u8, providers_count);
+                for i in 0..providers_count {
+                    let offset: usize = 264 * i as usize;
+                    let mut provider = PosComputeProvider {
+                        id: buffer[offset] as u32,
+                        model: "".to_string(),
+                        compute_api: buffer[offset + 260] as u32,
+                    };
+                    for j in 4..260 {
+                        let c: u8 = buffer[offset + j];
+                        if c == 0 {
+                            break;
+                        }
+                        provider.model.push(c as char);
+                    }
+                    dst.push(provider);
+                }
+            }
+
+            dst
+        }
+    }
+
+    /// Compute Spacemesh proof of space v0.1.0.
+    /// The hashes of the request's positions are written to the start of out which must be at least
+    /// the request's out_size bytes.
+    pub fn compute(
+        &self,
+        request: &ComputeRequest,
+        out: &mut [u8],
+    ) -> Result<ComputeOutcome, ComputeError> {
+        request.check(out.len())?;
+
+        let mut idx_solution: u64 = u64::MAX;
+        let mut hashes_computed: u64 = 0;
+        let mut hashes_per_sec: u64 = 0;
+        let res = unsafe {
+            (self.scrypt_positions)(
+                request.provider_id,
+                request.id.as_ptr(),
+                request.start_position,
+                request.end_position,
+                request.hash_len_bits,
+                request.salt.as_ptr(),
+                request.options,
+                out.as_mut_ptr(),
+                request.n,
+                request.r,
+                request.p,
+                request.d.as_ptr(),
+                &mut idx_solution,
+                &mut hashes_computed,
+                &mut hashes_per_sec,
+            )
+        };
+
+        if let Some(err) = ComputeError::from_code(res) {
+            return Err(err);
+        }
+        Ok(ComputeOutcome {
+            solution: match res {
+                SPACEMESH_API_POW_SOLUTION_FOUND => Some(idx_solution),
+                _ => None,
+            },
+            hashes_computed,
+            hashes_per_sec,
+        })
+    }
+
+    /// Stop all gpu work of the lib's providers
+    pub fn stop(&self, ms_timeout: u32) -> Result<(), ComputeError> {
+        match ComputeError::from_code(unsafe { (self.stop)(ms_timeout) }) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns true while a stop is in progress
+    pub fn stop_in_progress(&self) -> bool {
+        unsafe { (self.stop_inprogress)() != 0 }
+    }
+
+    /// Enable or disable the lib's log output
+    pub fn set_logging(&self, enable: bool) {
+        unsafe { (self.logging)(enable as i32) }
+    }
+
+    /// Release the lib's providers before the process exits. The lib must not be used after it was shut down
+    pub fn shutdown(&self) {
+        unsafe { (self.shutdown)() }
+    }
+
+    /// Compute the unit test hashes of an input with a provider. Returns the computed hashes
+    pub fn unit_test_hash(
+        &self,
+        provider_id: u32,
+        input: &[u8; UNIT_TEST_INPUT_SIZE],
+    ) -> Result<Vec<u8>, ComputeError> {
+        let mut input = *input;
+        let mut hashes = vec![0_u8; UNIT_TEST_HASHES * HASH_SIZE];
+        let res =
+            unsafe { (self.unit_test_hash)(provider_id, input.as_mut_ptr(), hashes.as_mut_ptr()) };
+        let count = GpuSetup::unit_test_result(res, UNIT_TEST_HASHES)?;
+        hashes.truncate(count * HASH_SIZE);
+        Ok(hashes)
+    }
+
+    /// Write the first hash_len_bits bits of each of the 32 bytes hashes to a bit stream with a provider.
+    /// Returns the bit stream
+    pub fn unit_test_bit_stream(
+        &self,
+        provider_id: u32,
+        hashes: &[u8],
+        hash_len_bits: u32,
+    ) -> Result<Vec<u8>, ComputeError> {
+        if hash_len_bits == 0 || hash_len_bits > 256 {
+            return Err(ComputeError::InvalidRequest(format!(
+                "hash_len_bits {} is not in [1, 256]",
+                hash_len_bits
+            )));
+        }
+        let count = hashes.len() / HASH_SIZE;
+        if count * HASH_SIZE != hashes.len() {
+            return Err(ComputeError::InvalidRequest(format!(
+                "hashes size {} is not a multiple of {}",
+                hashes.len(),
+                HASH_SIZE
+            )));
+        }
+        let mut hashes = hashes.to_vec();
+        let mut out = vec![0_u8; hashes.len()];
+        let res = unsafe {
+            (self.unit_test_bit_stream)(
+                provider_id,
+                hashes.as_mut_ptr(),
+                count as u64,
+                out.as_mut_ptr(),
+                hash_len_bits,
+            )
+        };
+        let len = GpuSetup::unit_test_result(res, out.len())?;
+        out.truncate(len);
+        Ok(out)
+    }
+
+    /// Run the gpu-setup-test self tests with a provider. The unit test hashes of a zeros input must be the
+    /// test vector hashes and the bit stream of each hash length must have the hashes' prefix bits size.
+    /// Bit streams of whole bytes prefixes, such as the 8 bits labels of pos data, must be the test vector
+    /// hashes' prefix bytes.
+    pub fn self_test(&self, provider_id: u32) -> Result<(), ComputeError> {
+        let hashes = self.unit_test_hash(provider_id, &[0; UNIT_TEST_INPUT_SIZE])?;
+        if hashes[..] != UNIT_TEST_HASHES_RESULT[..] {
+            return Err(ComputeError::SelfTestFailed(format!(
+                "{} of {} hashes were computed and they don't match the test vector",
+                hashes.len() / HASH_SIZE,
+                UNIT_TEST_HASHES
+            )));
+        }
+        for hash_len_bits in 1..=256 {
+            let stream = self.unit_test_bit_stream(provider_id, &hashes, hash_len_bits)?;
+            let expected = UNIT_TEST_HASHES * hash_len_bits as usize / 8;
+            if stream.len() != expected {
+                return Err(ComputeError::SelfTestFailed(format!(
+                    "{} bits stream size is {} bytes instead of {} bytes",
+                    hash_len_bits,
+                    stream.len(),
+                    expected
+                )));
+            }
+
+            if hash_len_bits % 8 == 0 {
+                let prefix_len = hash_len_bits as usize / 8;
+                let prefixes = UNIT_TEST_HASHES_RESULT
+                    .chunks(HASH_SIZE)
+                    .flat_map(|hash| &hash[..prefix_len]);
+                if !stream.iter().eq(prefixes) {
+                    return Err(ComputeError::SelfTestFailed(format!(
+                        "{} bits stream doesn't match the test vector hashes' prefixes",
+                        hash_len_bits
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the size of a unit test's result or its error. A result must not be larger than max
+    fn unit_test_result(res: i64, max: usize) -> Result<usize, ComputeError> {
+        if res < 0 {
+            return Err(ComputeError::from_code(res as i32).unwrap_or(ComputeError::Error));
+        }
+        match res as usize {
+            size if size > max => Err(ComputeError::Unknown(res as i32)),
+            size => Ok(size),
+        }
+    }
+}
+
+#[cfg(unix)]
+mod os {
+    use std::ffi::{c_void, CStr, CString};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    pub(super) type Handle = *mut c_void;
+
+    pub(super) unsafe fn open(path: &Path) -> Result<Handle, String> {
+        let path = CString::new(path.as_os_str().as_bytes()).map_err(|e| e.to_string())?;
+        let handle = libc::dlopen(path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL);
+        if handle.is_null() {
+            let error = libc::dlerror();
+            return Err(match error.is_null() {
+                true => "unknown error".to_string(),
+                false => CStr::from_ptr(error).to_string_lossy().into_owned(),
+            });
+        }
+        Ok(handle)
+    }
+
+    pub(super) unsafe fn symbol(handle: Handle, name: &str) -> *mut c_void {
+        let name = CString::new(name).unwrap();
+        libc::dlsym(handle, name.as_ptr())
+    }
+
+    pub(super) unsafe fn close(handle: Handle) {
+        libc::dlclose(handle);
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use std::ffi::{c_void, CString};
+    use std::iter;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use winapi::shared::minwindef::HMODULE;
+    use winapi::um::errhandlingapi::GetLastError;
+    use winapi::um::libloaderapi::{FreeLibrary, GetProcAddress, LoadLibraryW};
+
+    pub(super) type Handle = HMODULE;
+
+    pub(super) unsafe fn open(path: &Path) -> Result<Handle, String> {
+        let path: Vec<u16> = path
+            .as_os_str()
+            .encode_wide()
+            .chain(iter::once(0))
+            .collect();
+        let handle = LoadLibraryW(path.as_ptr());
+        if handle.is_null() {
+            return Err(format!("windows error code {}", GetLastError()));
+        }
+        Ok(handle)
+    }
+
+    pub(super) unsafe fn symbol(handle: Handle, name: &str) -> *mut c_void {
+        let name = CString::new(name).unwrap();
+        GetProcAddress(handle, name.as_ptr()) as *mut c_void
+    }
+
+    pub(super) unsafe fn close(handle: Handle) {
+        FreeLibrary(handle);
+    }
+}
@@ -1,11 +1,31 @@
-use std::ptr;
+use std::fmt;
 use std::str;
 
+mod backend;
+mod gpu_setup;
+mod mock;
+mod scrypt;
+mod scrypt_backend;
+
+pub use backend::{ComputeBackend, FfiBackend};
+pub use gpu_setup::{
+    GpuSetup, LibraryError, GPU_SETUP_LIB_ENV, UNIT_TEST_HASHES, UNIT_TEST_INPUT_SIZE,
+};
+pub use mock::{MockBackend, MockProvider};
+pub use scrypt_backend::ScryptBackend;
+
+/// pos-compute crate version
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 pub const SPACEMESH_API_ERROR_NONE: i32 = 0;
 pub const SPACEMESH_API_ERROR: i32 = -1;
 pub const SPACEMESH_API_ERROR_TIMEOUT: i32 = -2;
 pub const SPACEMESH_API_ERROR_ALREADY: i32 = -3;
 pub const SPACEMESH_API_ERROR_CANCELED: i32 = -4;
+pub const SPACEMESH_API_ERROR_NO_COMPUTE_OPTIONS: i32 = -5;
+pub const SPACEMESH_API_ERROR_INVALID_PARAMETER: i32 = -6;
+pub const SPACEMESH_API_ERROR_INVALID_PROVIDER_ID: i32 = -7;
+pub const SPACEMESH_API_POW_SOLUTION_FOUND: i32 = 1;
 pub const COMPUTE_API_CLASS_UNSPECIFIED: u32 = 0;
 pub const COMPUTE_API_CLASS_CPU: u32 = 1; // useful for testing on systems without a cuda or vulkan GPU
 pub const COMPUTE_API_CLASS_CUDA: u32 = 2;
@@ -17,163 +37,187 @@ pub enum OPTIONS {
     Throttle = 0x00008000,
 }
 
+#[derive(Debug, Clone)]
 pub struct PosComputeProvider {
     pub id: u32,          // 0, 1, 2...
     pub model: String,    // e.g. Nvidia GTX 2700
     pub compute_api: u32, // A provided compute api
 }
 
-#[link(name = "gpu-setup")]
-extern "C" {
-    fn scryptPositions(
-        provider_id: u32,       // POST compute provider ID
-        id: *const u8,          // 32 bytes
-        start_position: u64,    // e.g. 0
-        end_position: u64,      // e.g. 49,999
-        hash_len_bits: u32, // (1...256) for each hash output, the number of prefix bits (not bytes) to copy into the buffer
-        salt: *const u8,    // 32 bytes
-        options: u32,       // throttle, leafs, pow etc.
-        out: *mut u8, // memory buffer large enough to include hash_len_bits * number of requested hashes
-        N: u32,       // scrypt N
-        R: u32,       // scrypt r
-        P: u32,       // scrypt p
-        D: *const u8, // Target D for the POW computation. 32 bytes.
-        idx_solution: *mut u64, // pow solution index
-        hashes_computed: *mut u64,
-        hashes_per_sec: *mut u64,
-    ) -> i32;
-
-    // stop all GPU work and don't fill the passed-in buffer with any more results.
-    fn stop(ms_timeout: u32, // timeout in milliseconds
-    ) -> i32;
-
-    // return non-zero if stop in progress
-    fn spacemesh_api_stop_inprogress() -> i32;
-
-    // return POST compute providers info
-    fn spacemesh_api_get_providers(
-        providers: *mut u8, // out providers info buffer, if NULL - return count of available providers
-        max_providers: i32, // buffer size
-    ) -> i32;
+/// Size in bytes of a compute request's id, salt and pow difficulty
+pub const HASH_SIZE: usize = 32;
+
+/// A compute lib call error. Each SPACEMESH_API_ERROR_* code has its own error.
+/// Requests which fail the checks made before calling the lib are InvalidRequest errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComputeError {
+    Error,
+    Timeout,
+    Already,
+    Canceled,
+    NoComputeOptions,
+    InvalidParameter,
+    InvalidProviderId,
+    /// a result code which is unknown to this crate
+    Unknown(i32),
+    /// the request was not sent to the lib
+    InvalidRequest(String),
+    /// a provider's self test computed wrong results
+    SelfTestFailed(String),
 }
 
-pub fn get_providers() -> Vec<PosComputeProvider> {
-    unsafe {
-        let p: *mut u8 = ptr::null_mut();
-        let providers_count = spacemesh_api_get_providers(p, 0);
-        let mut dst: Vec<PosComputeProvider> = Vec::with_capacity(providers_count as usize);
-        if providers_count > 0 {
-            let mut buffer: Vec<u8> = Vec::new();
-            buffer.resize((providers_count * 264) as usize, 0);
-            let pdst = buffer.as_mut_ptr();
-            spacemesh_api_get_providers(pdst as *mut u8, providers_count);
-            for i in 0..providers_count {
-                let offset: usize = 264 * i as usize;
-                let mut provider = PosComputeProvider {
-                    id: buffer[offset + 0] as u32,
-                    model: "".to_string(),
-                    compute_api: buffer[offset + 260] as u32,
-                };
-                for j in 4..260 {
-                    let c: u8 = buffer[offset + j];
-                    if c == 0 {
-                        break;
-                    }
-                    provider.model.push(c as char);
-                }
-                dst.push(provider);
+impl ComputeError {
+    /// Returns the error of a compute lib result code or None for a successful result
+    pub fn from_code(code: i32) -> Option<ComputeError> {
+        match code {
+            SPACEMESH_API_ERROR_NONE | SPACEMESH_API_POW_SOLUTION_FOUND => None,
+            SPACEMESH_API_ERROR => Some(ComputeError::Error),
+            SPACEMESH_API_ERROR_TIMEOUT => Some(ComputeError::Timeout),
+            SPACEMESH_API_ERROR_ALREADY => Some(ComputeError::Already),
+            SPACEMESH_API_ERROR_CANCELED => Some(ComputeError::Canceled),
+            SPACEMESH_API_ERROR_NO_COMPUTE_OPTIONS => Some(ComputeError::NoComputeOptions),
+            SPACEMESH_API_ERROR_INVALID_PARAMETER => Some(ComputeError::InvalidParameter),
+            SPACEMESH_API_ERROR_INVALID_PROVIDER_ID => Some(ComputeError::InvalidProviderId),
+            _ => Some(ComputeError::Unknown(code)),
+        }
+    }
+
+    /// Returns the compute lib result code of the error. Invalid requests have the invalid parameter code
+    pub fn code(&self) -> i32 {
+        match self {
+            ComputeError::Error => SPACEMESH_API_ERROR,
+            ComputeError::Timeout => SPACEMESH_API_ERROR_TIMEOUT,
+            ComputeError::Already => SPACEMESH_API_ERROR_ALREADY,
+            ComputeError::Canceled => SPACEMESH_API_ERROR_CANCELED,
+            ComputeError::NoComputeOptions => SPACEMESH_API_ERROR_NO_COMPUTE_OPTIONS,
+            ComputeError::InvalidParameter | ComputeError::InvalidRequest(_) => {
+                SPACEMESH_API_ERROR_INVALID_PARAMETER
             }
+            ComputeError::InvalidProviderId => SPACEMESH_API_ERROR_INVALID_PROVIDER_ID,
+            ComputeError::Unknown(code) => *code,
+            ComputeError::SelfTestFailed(_) => SPACEMESH_API_ERROR,
         }
+    }
+}
 
-        dst
+impl fmt::Display for ComputeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComputeError::Error => write!(f, "compute error"),
+            ComputeError::Timeout => write!(f, "timeout"),
+            ComputeError::Already => write!(f, "a computation is already in progress"),
+            ComputeError::Canceled => write!(f, "canceled"),
+            ComputeError::NoComputeOptions => write!(f, "no compute options"),
+            ComputeError::InvalidParameter => write!(f, "invalid parameter"),
+            ComputeError::InvalidProviderId => write!(f, "invalid provider id"),
+            ComputeError::Unknown(code) => write!(f, "unknown compute result {}", code),
+            ComputeError::InvalidRequest(msg) => write!(f, "invalid compute request: {}", msg),
+            ComputeError::SelfTestFailed(msg) => write!(f, "self test failed: {}", msg),
+        }
     }
 }
 
-pub fn stop_inprogress() -> i32 {
-    unsafe { spacemesh_api_stop_inprogress() }
+impl std::error::Error for ComputeError {}
+
+/// A request to compute the hashes of the positions range [start_position, end_position]
+#[derive(Debug, Clone, Default)]
+pub struct ComputeRequest {
+    pub provider_id: u32,    // POST compute provider ID
+    pub id: [u8; HASH_SIZE], // client id
+    pub start_position: u64, // e.g. 0
+    pub end_position: u64,   // e.g. 49,999
+    pub hash_len_bits: u32, // (1...256) for each hash output, the number of prefix bits (not bytes) to copy into the buffer
+    pub salt: [u8; HASH_SIZE],
+    pub options: u32,       // throttle, leafs, pow etc.
+    pub n: u32,             // scrypt N
+    pub r: u32,             // scrypt r
+    pub p: u32,             // scrypt p
+    pub d: [u8; HASH_SIZE], // Target D for the POW computation. eg. 0x0ff...
 }
 
-pub fn stop_providers(ms_timeout: u32) -> i32 {
-    unsafe { stop(ms_timeout) }
+impl ComputeRequest {
+    /// Returns the size in bytes of the hashes of the request's positions
+    pub fn out_size(&self) -> usize {
+        let positions = self.end_position - self.start_position + 1;
+        ((positions as u128 * self.hash_len_bits as u128 + 7) / 8) as usize
+    }
+
+    /// Check the request before it is sent to the lib with an out buffer of out_len bytes
+    fn check(&self, out_len: usize) -> Result<(), ComputeError> {
+        if self.start_position > self.end_position {
+            return Err(ComputeError::InvalidRequest(format!(
+                "start position {} is after end position {}",
+                self.start_position, self.end_position
+            )));
+        }
+        if self.hash_len_bits == 0 || self.hash_len_bits > 256 {
+            return Err(ComputeError::InvalidRequest(format!(
+                "hash length must be 1 to 256 bits. Got {}",
+                self.hash_len_bits
+            )));
+        }
+        if out_len < self.out_size() {
+            return Err(ComputeError::InvalidRequest(format!(
+                "out buffer is {} bytes. {} bytes are required",
+                out_len,
+                self.out_size()
+            )));
+        }
+        Ok(())
+    }
 }
 
-/// Compute Spacemesh proof of space v0.1.0
-pub fn compute_pos(
-    provider_id: u32,          // POST compute provider ID
-    id: &[u8],                 // 32 bytes
-    start_position: u64,       // e.g. 0
-    end_position: u64,         // e.g. 49,999
-    hash_len_bits: u32, // (1...256) for each hash output, the number of prefix bits (not bytes) to copy into the buffer
-    salt: &[u8],        // 32 bytes
-    options: u32,       // throttle etc.
-    out: &mut [u8], // memory buffer large enough to include hash_len_bits * number of requested hashes
-    n: u32,         // scrypt N
-    r: u32,         // scrypt r
-    p: u32,         // scrypt p
-    d: &[u8],       // Target D for the POW computation. 32 bytes. eg. 0x0ff...
-    idx_solution: *mut u64, // POW computation solution index
-    hashes_computed: *mut u64, //
-    hashes_per_sec: *mut u64, //
-) -> i32 {
-    unsafe {
-        scryptPositions(
-            provider_id,
-            id.as_ptr(),
-            start_position,
-            end_position,
-            hash_len_bits,
-            salt.as_ptr(),
-            options,
-            out.as_mut_ptr(),
-            n,
-            r,
-            p,
-            d.as_ptr(),
-            idx_solution,
-            hashes_computed,
-            hashes_per_sec,
-        )
+/// Write the first bits of a hash to out at a bit offset. Bits are packed from each byte's least significant bit
+fn write_bits(out: &mut [u8], offset: u64, hash: &[u8], bits: u32) {
+    for i in 0..bits as u64 {
+        let bit = (hash[(i / 8) as usize] >> (i % 8)) & 1;
+        let pos = offset + i;
+        let byte = &mut out[(pos / 8) as usize];
+        *byte = (*byte & !(1 << (pos % 8))) | (bit << (pos % 8));
     }
 }
 
+/// The outcome of a successful compute request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComputeOutcome {
+    /// index of the first pow solution of the positions range when one was found
+    pub solution: Option<u64>,
+    pub hashes_computed: u64,
+    pub hashes_per_sec: u64,
+}
+
 // Utility functions and helpers below
 
 const LABEL_SIZE: u32 = 8;
 const LABELS_COUNT: u64 = 9 * 128 * 1024;
 
-pub fn do_benchmark() {
-    let id: [u8; 32] = [0; 32];
-    let salt: [u8; 32] = [0; 32];
-    let d: [u8; 32] = [0; 32];
-
-    let providers = get_providers();
+pub fn do_benchmark(lib: &GpuSetup) {
+    let providers = lib.providers();
 
     if providers.len() > 0 {
         const OUT_SIZE: usize = (LABELS_COUNT as usize * LABEL_SIZE as usize + 7) / 8;
         let mut out = vec![0_u8; OUT_SIZE];
         for provider in &providers {
             if provider.compute_api as u32 != COMPUTE_API_CLASS_CPU {
-                let mut hashes_computed: u64 = 0;
-                let mut hashes_per_sec: u64 = 0;
-                let mut idx_solution: u64 = 0;
-
-                let status = compute_pos(
-                    provider.id,
-                    &id,
-                    0,
-                    LABELS_COUNT as u64 - 1,
-                    LABEL_SIZE,
-                    &salt,
-                    OPTIONS::ComputeLeaves as u32,
-                    &mut out,
-                    512,
-                    1,
-                    1,
-                    &d,
-                    &mut idx_solution as *mut u64,
-                    &mut hashes_computed as *mut u64,
-                    &mut hashes_per_sec as *mut u64,
-                );
+                let request = ComputeRequest {
+                    provider_id: provider.id,
+                    end_position: LABELS_COUNT - 1,
+                    hash_len_bits: LABEL_SIZE,
+                    options: OPTIONS::ComputeLeaves as u32,
+                    n: 512,
+                    r: 1,
+                    p: 1,
+                    ..Default::default()
+                };
+                let (status, hashes_computed, hashes_per_sec) =
+                    match lib.compute(&request, &mut out) {
+                        Ok(outcome) => (
+                            SPACEMESH_API_ERROR_NONE,
+                            outcome.hashes_computed,
+                            outcome.hashes_per_sec,
+                        ),
+                        Err(e) => (e.code(), 0, 0),
+                    };
 
                 println!(
                     "{}: status: {} hashes: {} ({} h/s)",
@@ -184,6 +228,16 @@ pub fn do_benchmark() {
     }
 }
 
+/// Run the self tests of all of the lib's providers
+pub fn do_self_tests(lib: &GpuSetup) {
+    for provider in &lib.providers() {
+        match lib.self_test(provider.id) {
+            Ok(()) => println!("{}: self tests OK", provider.model),
+            Err(e) => println!("{}: {}", provider.model, e),
+        }
+    }
+}
+
 fn get_provider_class_string(class: u32) -> &'static str {
     match class {
         COMPUTE_API_CLASS_UNSPECIFIED => "UNSPECIFIED",
@@ -194,8 +248,8 @@ fn get_provider_class_string(class: u32) -> &'static str {
     }
 }
 
-pub fn do_providers_list() {
-    let providers = get_providers();
+pub fn do_providers_list(lib: &GpuSetup) {
+    let providers = lib.providers();
     println!("available pos compute providers:");
     for provider in &providers {
         println!(
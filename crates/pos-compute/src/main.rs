@@ -1,7 +1,22 @@
 extern crate pos_compute;
 
-// basic example app of using the lib
+use pos_compute::GpuSetup;
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+// basic example app of using the lib. The gpu-setup lib path may be provided as the first arg
 fn main() {
-    pos_compute::do_providers_list();
-    pos_compute::do_benchmark();
+    let path = env::args().nth(1).map(PathBuf::from);
+    let lib = match GpuSetup::load(path.as_deref()) {
+        Ok(lib) => lib,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+    pos_compute::do_providers_list(lib);
+    pos_compute::do_self_tests(lib);
+    pos_compute::do_benchmark(lib);
+    lib.shutdown();
 }
@@ -0,0 +1,228 @@
+use crate::backend::InProgress;
+use crate::{
+    write_bits, ComputeBackend, ComputeError, ComputeOutcome, ComputeRequest, PosComputeProvider,
+    HASH_SIZE, OPTIONS,
+};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hasher;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest sleep of a mock computation between checks for a stop
+const MOCK_STOP_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A mock compute provider
+#[derive(Debug, Clone)]
+pub struct MockProvider {
+    pub model: String,
+    pub compute_api: u32,
+    /// simulated hash rate. Computations complete without a delay when 0
+    pub hashes_per_sec: u64,
+}
+
+/// An in-memory compute backend for tests which don't have a compute lib or gpus.
+/// Computed hashes are deterministic but they are not scrypt hashes.
+/// Failures of a provider's computations may be injected.
+pub struct MockBackend {
+    providers: Vec<MockProvider>,
+    failures: Mutex<HashMap<u32, (ComputeError, u32)>>, // provider failure and its remaining count
+    self_test_failures: Mutex<HashSet<u32>>,            // providers which fail self tests
+    stops: AtomicU64,     // computations started before the last stop are canceled
+    stopping: AtomicBool, // a stop is waiting for in-progress computations
+    in_progress: AtomicUsize, // number of in-progress computations
+}
+
+impl MockBackend {
+    /// Create a backend of providers. A provider's id is its index
+    pub fn new(providers: Vec<MockProvider>) -> Self {
+        MockBackend {
+            providers,
+            failures: Mutex::new(HashMap::new()),
+            self_test_failures: Mutex::new(HashSet::new()),
+            stops: AtomicU64::new(0),
+            stopping: AtomicBool::new(false),
+            in_progress: AtomicUsize::new(0),
+        }
+    }
+
+    /// Fail the next count computations of a provider with an error. The provider always fails when count is u32::MAX
+    pub fn inject_failures(&self, provider_id: u32, error: ComputeError, count: u32) {
+        let mut failures = self.failures.lock().unwrap();
+        match count {
+            0 => failures.remove(&provider_id),
+            _ => failures.insert(provider_id, (error, count)),
+        };
+    }
+
+    /// Fail the self tests of a provider
+    pub fn inject_self_test_failure(&self, provider_id: u32) {
+        self.self_test_failures.lock().unwrap().insert(provider_id);
+    }
+
+    /// Returns the injected failure of a provider's next computation
+    fn next_failure(&self, provider_id: u32) -> Option<ComputeError> {
+        let mut failures = self.failures.lock().unwrap();
+        let (error, count) = failures.get_mut(&provider_id)?;
+        let error = error.clone();
+        if *count != u32::MAX {
+            *count -= 1;
+            if *count == 0 {
+                failures.remove(&provider_id);
+            }
+        }
+        Some(error)
+    }
+
+    /// Returns the mock hash of a request's position
+    fn hash(request: &ComputeRequest, position: u64) -> [u8; HASH_SIZE] {
+        let mut hash = [0_u8; HASH_SIZE];
+        for (i, chunk) in hash.chunks_mut(8).enumerate() {
+            let mut hasher = DefaultHasher::new();
+            hasher.write(&request.id);
+            hasher.write(&request.salt);
+            hasher.write_u64(position);
+            hasher.write_u32(request.n);
+            hasher.write_u32(request.r);
+            hasher.write_u32(request.p);
+            hasher.write_usize(i);
+            chunk.copy_from_slice(&hasher.finish().to_be_bytes());
+        }
+        hash
+    }
+
+    /// Wait for a computation of hashes which started at the stops count to complete at the provider's hash rate.
+    /// Returns false if the computation was stopped
+    fn wait(&self, provider: &MockProvider, hashes: u64, stops: u64) -> bool {
+        let duration = match provider.hashes_per_sec {
+            0 => Duration::from_secs(0),
+            rate => Duration::from_secs_f64(hashes as f64 / rate as f64),
+        };
+        let started = Instant::now();
+        loop {
+            if self.stops.load(Ordering::SeqCst) != stops {
+                return false;
+            }
+            let elapsed = started.elapsed();
+            if elapsed >= duration {
+                return true;
+            }
+            thread::sleep((duration - elapsed).min(MOCK_STOP_CHECK_INTERVAL));
+        }
+    }
+}
+
+impl ComputeBackend for MockBackend {
+    fn providers(&self) -> Vec<PosComputeProvider> {
+        self.providers
+            .iter()
+            .enumerate()
+            .map(|(id, p)| PosComputeProvider {
+                id: id as u32,
+                model: p.model.clone(),
+                compute_api: p.compute_api,
+            })
+            .collect()
+    }
+
+    fn compute(
+        &self,
+        request: &ComputeRequest,
+        out: &mut [u8],
+    ) -> Result<ComputeOutcome, ComputeError> {
+        request.check(out.len())?;
+        let provider = match self.providers.get(request.provider_id as usize) {
+            Some(provider) => provider,
+            None => return Err(ComputeError::InvalidProviderId),
+        };
+        let compute_leaves = request.options & OPTIONS::ComputeLeaves as u32 != 0;
+        let compute_pow = request.options & OPTIONS::ComputePow as u32 != 0;
+        if !compute_leaves && !compute_pow {
+            return Err(ComputeError::NoComputeOptions);
+        }
+        if let Some(error) = self.next_failure(request.provider_id) {
+            return Err(error);
+        }
+
+        let _in_progress = InProgress::start(&self.in_progress);
+        let stops = self.stops.load(Ordering::SeqCst);
+        let started = Instant::now();
+        let mut solution = None;
+        let mut hashes_computed: u64 = 0;
+        for position in request.start_position..=request.end_position {
+            let hash = MockBackend::hash(request, position);
+            hashes_computed += 1;
+            if compute_leaves {
+                let offset = (position - request.start_position) * request.hash_len_bits as u64;
+                write_bits(out, offset, &hash, request.hash_len_bits);
+            }
+            if compute_pow && solution.is_none() && hash < request.d {
+                solution = Some(position);
+                if !compute_leaves {
+                    // a pow search stops at its first solution
+                    break;
+                }
+            }
+        }
+        let completed = self.wait(provider, hashes_computed, stops);
+        if !completed {
+            return Err(ComputeError::Canceled);
+        }
+
+        let secs = started.elapsed().as_secs_f64();
+        Ok(ComputeOutcome {
+            solution,
+            hashes_computed,
+            hashes_per_sec: match provider.hashes_per_sec {
+                0 if secs > 0.0 => (hashes_computed as f64 / secs) as u64,
+                0 => u64::MAX,
+                rate => rate,
+            },
+        })
+    }
+
+    fn stop(&self, ms_timeout: u32) -> Result<(), ComputeError> {
+        self.stopping.store(true, Ordering::SeqCst);
+        self.stops.fetch_add(1, Ordering::SeqCst);
+        let started = Instant::now();
+        let timeout = Duration::from_millis(ms_timeout as u64);
+        let res = loop {
+            if self.in_progress.load(Ordering::SeqCst) == 0 {
+                break Ok(());
+            }
+            if started.elapsed() >= timeout {
+                break Err(ComputeError::Timeout);
+            }
+            thread::sleep(MOCK_STOP_CHECK_INTERVAL);
+        };
+        self.stopping.store(false, Ordering::SeqCst);
+        res
+    }
+
+    fn stop_in_progress(&self) -> bool {
+        self.stopping.load(Ordering::SeqCst)
+    }
+
+    fn shutdown(&self) -> Result<(), ComputeError> {
+        self.stop(0)
+    }
+
+    fn self_test(&self, provider_id: u32) -> Result<(), ComputeError> {
+        if provider_id as usize >= self.providers.len() {
+            return Err(ComputeError::InvalidProviderId);
+        }
+        match self
+            .self_test_failures
+            .lock()
+            .unwrap()
+            .contains(&provider_id)
+        {
+            true => Err(ComputeError::SelfTestFailed(
+                "injected self test failure".to_string(),
+            )),
+            false => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,378 @@
+//! A pure Rust scrypt (RFC 7914) with HMAC-SHA256 PBKDF2 and Salsa20/8 block mixing
+
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+const SHA256_INIT: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// Incremental SHA256
+#[derive(Clone)]
+struct Sha256 {
+    state: [u32; 8],
+    block: [u8; 64],
+    block_len: usize,
+    len: u64,
+}
+
+impl Sha256 {
+    fn new() -> Self {
+        Sha256 {
+            state: SHA256_INIT,
+            block: [0; 64],
+            block_len: 0,
+            len: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.len += data.len() as u64;
+        while !data.is_empty() {
+            let n = (64 - self.block_len).min(data.len());
+            self.block[self.block_len..self.block_len + n].copy_from_slice(&data[..n]);
+            self.block_len += n;
+            data = &data[n..];
+            if self.block_len == 64 {
+                let block = self.block;
+                self.compress(&block);
+                self.block_len = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> [u8; 32] {
+        let bits = self.len * 8;
+        self.update(&[0x80]);
+        while self.block_len != 56 {
+            self.update(&[0]);
+        }
+        self.update(&bits.to_be_bytes());
+        let mut res = [0_u8; 32];
+        for (chunk, word) in res.chunks_mut(4).zip(self.state.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        res
+    }
+
+    fn compress(&mut self, block: &[u8; 64]) {
+        let mut w = [0_u32; 64];
+        for (i, chunk) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(SHA256_K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (s, v) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+            *s = s.wrapping_add(*v);
+        }
+    }
+}
+
+/// HMAC-SHA256 keyed with a password. The keyed inner and outer hashes are reused for each message
+struct HmacSha256 {
+    inner: Sha256,
+    outer: Sha256,
+}
+
+impl HmacSha256 {
+    fn new(key: &[u8]) -> Self {
+        let mut block = [0_u8; 64];
+        if key.len() > 64 {
+            let mut hasher = Sha256::new();
+            hasher.update(key);
+            block[..32].copy_from_slice(&hasher.finish());
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+        let mut inner = Sha256::new();
+        let mut outer = Sha256::new();
+        inner.update(&block.map(|b| b ^ 0x36));
+        outer.update(&block.map(|b| b ^ 0x5c));
+        HmacSha256 { inner, outer }
+    }
+
+    fn mac(&self, parts: &[&[u8]]) -> [u8; 32] {
+        let mut inner = self.inner.clone();
+        for part in parts {
+            inner.update(part);
+        }
+        let mut outer = self.outer.clone();
+        outer.update(&inner.finish());
+        outer.finish()
+    }
+}
+
+/// PBKDF2-HMAC-SHA256 with a single iteration, which is the only iterations count used by scrypt
+fn pbkdf2_sha256(password: &[u8], salt: &[u8], out: &mut [u8]) {
+    let hmac = HmacSha256::new(password);
+    for (i, chunk) in out.chunks_mut(32).enumerate() {
+        let block = hmac.mac(&[salt, &(i as u32 + 1).to_be_bytes()]);
+        chunk.copy_from_slice(&block[..chunk.len()]);
+    }
+}
+
+/// The Salsa20/8 core applied to a 64 bytes block
+fn salsa20_8(block: &mut [u32; 16]) {
+    let mut x = *block;
+    for _ in 0..4 {
+        for &(a, b, c, d) in &[
+            (4, 0, 12, 7),
+            (8, 4, 0, 9),
+            (12, 8, 4, 13),
+            (0, 12, 8, 18),
+            (9, 5, 1, 7),
+            (13, 9, 5, 9),
+            (1, 13, 9, 13),
+            (5, 1, 13, 18),
+            (14, 10, 6, 7),
+            (2, 14, 10, 9),
+            (6, 2, 14, 13),
+            (10, 6, 2, 18),
+            (3, 15, 11, 7),
+            (7, 3, 15, 9),
+            (11, 7, 3, 13),
+            (15, 11, 7, 18),
+        ] {
+            x[a] ^= x[b].wrapping_add(x[c]).rotate_left(d);
+        }
+        for &(a, b, c, d) in &[
+            (1, 0, 3, 7),
+            (2, 1, 0, 9),
+            (3, 2, 1, 13),
+            (0, 3, 2, 18),
+            (6, 5, 4, 7),
+            (7, 6, 5, 9),
+            (4, 7, 6, 13),
+            (5, 4, 7, 18),
+            (11, 10, 9, 7),
+            (8, 11, 10, 9),
+            (9, 8, 11, 13),
+            (10, 9, 8, 18),
+            (12, 15, 14, 7),
+            (13, 12, 15, 9),
+            (14, 13, 12, 13),
+            (15, 14, 13, 18),
+        ] {
+            x[a] ^= x[b].wrapping_add(x[c]).rotate_left(d);
+        }
+    }
+    for (b, x) in block.iter_mut().zip(x.iter()) {
+        *b = b.wrapping_add(*x);
+    }
+}
+
+/// scryptBlockMix of 2 * r 64 bytes blocks. y is a scratch buffer of the blocks size
+fn block_mix(b: &mut [u32], y: &mut [u32]) {
+    let blocks = b.len() / 16;
+    let mut x = [0_u32; 16];
+    x.copy_from_slice(&b[(blocks - 1) * 16..]);
+    for i in 0..blocks {
+        for (x, b) in x.iter_mut().zip(b[i * 16..(i + 1) * 16].iter()) {
+            *x ^= b;
+        }
+        salsa20_8(&mut x);
+        // even blocks are placed first followed by odd blocks
+        let dst = (i / 2 + (i % 2) * blocks / 2) * 16;
+        y[dst..dst + 16].copy_from_slice(&x);
+    }
+    b.copy_from_slice(y);
+}
+
+/// scryptROMix of a 128 * r bytes block with n (a power of 2) 128 * r bytes memory blocks
+fn ro_mix(block: &mut [u8], n: usize, memory: &mut Vec<u32>) {
+    let words = block.len() / 4;
+    let mut x: Vec<u32> = block
+        .chunks(4)
+        .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect();
+    let mut y = vec![0_u32; words];
+    memory.resize(n * words, 0);
+
+    for i in 0..n {
+        memory[i * words..(i + 1) * words].copy_from_slice(&x);
+        block_mix(&mut x, &mut y);
+    }
+    for _ in 0..n {
+        // integerify: the first word of the last 64 bytes block
+        let j = x[words - 16] as usize & (n - 1);
+        for (x, v) in x.iter_mut().zip(memory[j * words..(j + 1) * words].iter()) {
+            *x ^= v;
+        }
+        block_mix(&mut x, &mut y);
+    }
+
+    for (chunk, word) in block.chunks_mut(4).zip(x.iter()) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+}
+
+/// scrypt key derivation of out.len() bytes.
+/// n must be a power of 2 larger than 1. memory is a reusable scratch buffer of 128 * r * n bytes
+pub(crate) fn scrypt(
+    password: &[u8],
+    salt: &[u8],
+    n: u32,
+    r: u32,
+    p: u32,
+    out: &mut [u8],
+    memory: &mut Vec<u32>,
+) {
+    let block_len = 128 * r as usize;
+    let mut b = vec![0_u8; block_len * p as usize];
+    pbkdf2_sha256(password, salt, &mut b);
+    for block in b.chunks_mut(block_len) {
+        ro_mix(block, n as usize, memory);
+    }
+    pbkdf2_sha256(password, &b, out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unhex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn sha256(data: &[u8]) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(data);
+        hasher.finish().to_vec()
+    }
+
+    fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+        HmacSha256::new(key).mac(&[data]).to_vec()
+    }
+
+    /// FIPS 180-2 SHA-256 examples and the empty message
+    #[test]
+    fn sha256_vectors() {
+        assert_eq!(
+            sha256(b""),
+            unhex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+        assert_eq!(
+            sha256(b"abc"),
+            unhex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_eq!(
+            sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            unhex("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1")
+        );
+
+        // a million a's hashed in updates which don't end on block boundaries
+        let mut hasher = Sha256::new();
+        for _ in 0..10000 {
+            hasher.update(&[b'a'; 100]);
+        }
+        assert_eq!(
+            hasher.finish().to_vec(),
+            unhex("cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0")
+        );
+    }
+
+    /// RFC 4231 HMAC-SHA256 test cases 1, 2, 6 and 7. Keys of cases 6 and 7 are longer than a block
+    #[test]
+    fn hmac_sha256_vectors() {
+        assert_eq!(
+            hmac_sha256(&[0x0b; 20], b"Hi There"),
+            unhex("b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7")
+        );
+        assert_eq!(
+            hmac_sha256(b"Jefe", b"what do ya want for nothing?"),
+            unhex("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843")
+        );
+        assert_eq!(
+            hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            ),
+            unhex("60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54")
+        );
+        assert_eq!(
+            hmac_sha256(
+                &[0xaa; 131],
+                b"This is a test using a larger than block-size key and a larger than block-size data. \
+                The key needs to be hashed before being used by the HMAC algorithm."
+            ),
+            unhex("9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2")
+        );
+    }
+
+    /// RFC 7914 PBKDF2-HMAC-SHA256 test vector with a single iteration
+    #[test]
+    fn pbkdf2_sha256_vector() {
+        let mut out = [0_u8; 64];
+        pbkdf2_sha256(b"passwd", b"salt", &mut out);
+        assert_eq!(
+            out.to_vec(),
+            unhex(
+                "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+                49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+            )
+        );
+    }
+
+    /// RFC 7914 scrypt test vectors. The memory buffer is reused between derivations
+    #[test]
+    fn scrypt_vectors() {
+        let mut memory = vec![];
+        let mut out = [0_u8; 64];
+        scrypt(b"", b"", 16, 1, 1, &mut out, &mut memory);
+        assert_eq!(
+            out.to_vec(),
+            unhex(
+                "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442\
+                fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906"
+            )
+        );
+
+        scrypt(b"password", b"NaCl", 1024, 8, 16, &mut out, &mut memory);
+        assert_eq!(
+            out.to_vec(),
+            unhex(
+                "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162\
+                2eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640"
+            )
+        );
+    }
+}
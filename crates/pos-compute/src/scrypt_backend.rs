@@ -0,0 +1,268 @@
+use crate::backend::InProgress;
+use crate::scrypt::scrypt;
+use crate::{
+    write_bits, ComputeBackend, ComputeError, ComputeOutcome, ComputeRequest, PosComputeProvider,
+    COMPUTE_API_CLASS_CPU, HASH_SIZE, OPTIONS,
+};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Positions computed by a worker thread between checks for a found pow solution.
+/// A multiple of 8 so workers write whole bytes of the out buffer
+const POSITIONS_PER_BATCH: u64 = 64;
+
+/// Interval of checks for in-progress computations while stopping them
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The RFC 7914 scrypt test vector of an empty password and salt with N=16, r=1, p=1
+const SELF_TEST_RESULT: [u8; 64] = [
+    0x77, 0xd6, 0x57, 0x62, 0x38, 0x65, 0x7b, 0x20, 0x3b, 0x19, 0xca, 0x42, 0xc1, 0x8a, 0x04, 0x97,
+    0xf1, 0x6b, 0x48, 0x44, 0xe3, 0x07, 0x4a, 0xe8, 0xdf, 0xdf, 0xfa, 0x3f, 0xed, 0xe2, 0x14, 0x42,
+    0xfc, 0xd0, 0x06, 0x9d, 0xed, 0x09, 0x48, 0xf8, 0x32, 0x6a, 0x75, 0x3a, 0x0f, 0xc8, 0x1f, 0x17,
+    0xe8, 0xd3, 0xe0, 0xfb, 0x2e, 0x0d, 0x36, 0x28, 0xcf, 0x35, 0xe2, 0x0c, 0x38, 0xd1, 0x89, 0x06,
+];
+
+/// A pure Rust compute backend with one cpu provider which computes labels with all cpu cores.
+/// The label of a position is the scrypt hash of id || position (8 bytes, little endian) with the salt,
+/// truncated to the request's hash_len_bits. Labels are packed to the out buffer from each byte's
+/// least significant bit. A position is a pow solution when its 32 bytes hash is smaller than D
+/// (both big endian).
+/// These labels are not the labels of the gpu-setup lib's scryptPositions, which doesn't document its scrypt
+/// input layout, so the data of a job must be computed by one of the two backends only.
+pub struct ScryptBackend {
+    threads: usize,
+    stops: AtomicU64,     // computations started before the last stop are canceled
+    stopping: AtomicBool, // a stop is waiting for in-progress computations
+    in_progress: AtomicUsize, // number of in-progress computations
+}
+
+impl Default for ScryptBackend {
+    fn default() -> Self {
+        ScryptBackend::new(0)
+    }
+}
+
+impl ScryptBackend {
+    /// Create a backend which computes with threads worker threads. All cpu cores are used when 0
+    pub fn new(threads: usize) -> Self {
+        let threads = match threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+        ScryptBackend {
+            threads,
+            stops: AtomicU64::new(0),
+            stopping: AtomicBool::new(false),
+            in_progress: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the 32 bytes label hash of a request's position
+    pub fn hash(request: &ComputeRequest, position: u64, memory: &mut Vec<u32>) -> [u8; HASH_SIZE] {
+        let mut password = [0_u8; HASH_SIZE + 8];
+        password[..HASH_SIZE].copy_from_slice(&request.id);
+        password[HASH_SIZE..].copy_from_slice(&position.to_le_bytes());
+        let mut hash = [0_u8; HASH_SIZE];
+        scrypt(
+            &password,
+            &request.salt,
+            request.n,
+            request.r,
+            request.p,
+            &mut hash,
+            memory,
+        );
+        hash
+    }
+
+    /// Compute the labels of a batch of positions starting at start to out which starts at the batch's
+    /// first label. Returns the first pow solution of the batch, or an error if the computation was stopped
+    fn compute_batch(
+        &self,
+        request: &ComputeRequest,
+        start: u64,
+        out: &mut [u8],
+        stops: u64,
+        memory: &mut Vec<u32>,
+    ) -> Result<Option<u64>, ComputeError> {
+        let compute_leaves = request.options & OPTIONS::ComputeLeaves as u32 != 0;
+        let compute_pow = request.options & OPTIONS::ComputePow as u32 != 0;
+        let end = (start + POSITIONS_PER_BATCH - 1).min(request.end_position);
+        let mut solution = None;
+        for position in start..=end {
+            if self.stops.load(Ordering::Relaxed) != stops {
+                return Err(ComputeError::Canceled);
+            }
+            let hash = ScryptBackend::hash(request, position, memory);
+            if compute_leaves {
+                let offset = (position - start) * request.hash_len_bits as u64;
+                write_bits(out, offset, &hash, request.hash_len_bits);
+            }
+            if compute_pow && solution.is_none() && hash < request.d {
+                solution = Some(position);
+                if !compute_leaves {
+                    break;
+                }
+            }
+        }
+        Ok(solution)
+    }
+}
+
+impl ComputeBackend for ScryptBackend {
+    fn providers(&self) -> Vec<PosComputeProvider> {
+        vec![PosComputeProvider {
+            id: 0,
+            model: format!("rust scrypt cpu ({} threads)", self.threads),
+            compute_api: COMPUTE_API_CLASS_CPU,
+        }]
+    }
+
+    fn compute(
+        &self,
+        request: &ComputeRequest,
+        out: &mut [u8],
+    ) -> Result<ComputeOutcome, ComputeError> {
+        request.check(out.len())?;
+        if request.provider_id != 0 {
+            return Err(ComputeError::InvalidProviderId);
+        }
+        if request.options & (OPTIONS::ComputeLeaves as u32 | OPTIONS::ComputePow as u32) == 0 {
+            return Err(ComputeError::NoComputeOptions);
+        }
+        if request.n < 2 || !request.n.is_power_of_two() || request.r == 0 || request.p == 0 {
+            return Err(ComputeError::InvalidParameter);
+        }
+
+        let _in_progress = InProgress::start(&self.in_progress);
+        let stops = self.stops.load(Ordering::SeqCst);
+        let started = Instant::now();
+        let compute_leaves = request.options & OPTIONS::ComputeLeaves as u32 != 0;
+        let batch_bytes = (POSITIONS_PER_BATCH * request.hash_len_bits as u64 / 8) as usize;
+        let batches = (request.end_position - request.start_position) / POSITIONS_PER_BATCH + 1;
+
+        // workers compute rounds of consecutive batches so a pow search stops at the round of its first solution
+        let mut res: Result<Option<u64>, ComputeError> = Ok(None);
+        let mut batch: u64 = 0;
+        let mut memories: Vec<Vec<u32>> = vec![vec![]; self.threads];
+        while batch < batches {
+            let round = (batches - batch).min(self.threads as u64) as usize;
+            let out_start = (batch as usize * batch_bytes).min(out.len());
+            let round_out = &mut out[out_start..];
+            let results: Vec<Result<Option<u64>, ComputeError>> = thread::scope(|scope| {
+                let workers: Vec<_> = round_out
+                    .chunks_mut(batch_bytes)
+                    .zip(memories.iter_mut())
+                    .take(round)
+                    .enumerate()
+                    .map(|(i, (batch_out, memory))| {
+                        let start =
+                            request.start_position + (batch + i as u64) * POSITIONS_PER_BATCH;
+                        scope.spawn(move || {
+                            self.compute_batch(request, start, batch_out, stops, memory)
+                        })
+                    })
+                    .collect();
+                // a panicked worker fails the computation instead of the caller
+                workers
+                    .into_iter()
+                    .map(|w| w.join().unwrap_or(Err(ComputeError::Error)))
+                    .collect()
+            });
+
+            for batch_res in results {
+                match (&res, batch_res) {
+                    (Ok(None), batch_res) => res = batch_res,
+                    (Ok(Some(_)), Err(e)) => res = Err(e),
+                    _ => {}
+                }
+            }
+            batch += round as u64;
+            if res.is_err() || (!compute_leaves && matches!(res, Ok(Some(_)))) {
+                break;
+            }
+        }
+
+        let solution = res?;
+        let hashes_computed = match (compute_leaves, solution) {
+            (false, Some(solution)) => solution - request.start_position + 1,
+            _ => request.end_position - request.start_position + 1,
+        };
+        let secs = started.elapsed().as_secs_f64();
+        Ok(ComputeOutcome {
+            solution,
+            hashes_computed,
+            hashes_per_sec: match secs > 0.0 {
+                true => (hashes_computed as f64 / secs) as u64,
+                false => hashes_computed,
+            },
+        })
+    }
+
+    fn stop(&self, ms_timeout: u32) -> Result<(), ComputeError> {
+        self.stopping.store(true, Ordering::SeqCst);
+        self.stops.fetch_add(1, Ordering::SeqCst);
+        let started = Instant::now();
+        let timeout = Duration::from_millis(ms_timeout as u64);
+        let res = loop {
+            if self.in_progress.load(Ordering::SeqCst) == 0 {
+                break Ok(());
+            }
+            if started.elapsed() >= timeout {
+                break Err(ComputeError::Timeout);
+            }
+            thread::sleep(STOP_CHECK_INTERVAL);
+        };
+        self.stopping.store(false, Ordering::SeqCst);
+        res
+    }
+
+    fn stop_in_progress(&self) -> bool {
+        self.stopping.load(Ordering::SeqCst)
+    }
+
+    fn shutdown(&self) -> Result<(), ComputeError> {
+        self.stop(0)
+    }
+
+    fn self_test(&self, provider_id: u32) -> Result<(), ComputeError> {
+        if provider_id != 0 {
+            return Err(ComputeError::InvalidProviderId);
+        }
+        let mut out = [0_u8; 64];
+        scrypt(&[], &[], 16, 1, 1, &mut out, &mut vec![]);
+        match out == SELF_TEST_RESULT {
+            true => Ok(()),
+            false => Err(ComputeError::SelfTestFailed(
+                "scrypt doesn't match the RFC 7914 test vector".to_string(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The labels of positions 0..65535 with a zeros id and salt, hash_len_bits 1 and N=512, computed by the
+    /// gpu-setup cpu provider. This is the test_vector_1_64k of the gpu-setup-test tool
+    const GPU_SETUP_TEST_VECTOR: &[u8; 8192] = include_bytes!("../resources/test_vector_1_64k.bin");
+
+    #[test]
+    #[ignore = "the scrypt input layout of the gpu-setup lib's scryptPositions is unknown"]
+    fn gpu_setup_test_vector() {
+        let request = ComputeRequest {
+            end_position: 511,
+            hash_len_bits: 1,
+            options: OPTIONS::ComputeLeaves as u32,
+            n: 512,
+            r: 1,
+            p: 1,
+            ..Default::default()
+        };
+        let mut out = vec![0_u8; request.out_size()];
+        ScryptBackend::new(0).compute(&request, &mut out).unwrap();
+        assert_eq!(out[..], GPU_SETUP_TEST_VECTOR[..out.len()]);
+    }
+}
@@ -1,14 +1,16 @@
 use crate::pos_api::api::pos_data_service_server::PosDataService;
 use crate::server::{
-    AbortJob, AddJob, GetAllJobs, GetAllProviders, GetConfig, GetJob, PosServer, SetConfig,
-    SubscribeToJobStatuses,
+    AbortJob, AddJob, AddJobError, DisableProviders, EnableProviders, GetAllJobs, GetAllProviders,
+    GetConfig, GetJob, MoveJob, PosServer, SetConfig, SetConfigError, SubscribeToJobStatuses,
 };
 use anyhow::Result;
 use pos_api::api::{
-    AbortJobRequest, AbortJobResponse, AddJobRequest, AddJobResponse, GetAllJobsStatusRequest,
-    GetAllJobsStatusResponse, GetConfigRequest, GetConfigResponse, GetJobStatusRequest,
-    GetJobStatusResponse, GetProvidersRequest, GetProvidersResponse, Job, JobStatusStreamRequest,
-    JobStatusStreamResponse, Provider, SetConfigRequest, SetConfigResponse,
+    AbortJobRequest, AbortJobResponse, AddJobRequest, AddJobResponse, DisableProviderRequest,
+    DisableProviderResponse, EnableProviderRequest, EnableProviderResponse,
+    GetAllJobsStatusRequest, GetAllJobsStatusResponse, GetConfigRequest, GetConfigResponse,
+    GetJobStatusRequest, GetJobStatusResponse, GetProvidersRequest, GetProvidersResponse, Job,
+    JobStatusStreamRequest, JobStatusStreamResponse, MoveJobRequest, MoveJobResponse, Provider,
+    SetConfigRequest, SetConfigResponse,
 };
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Status};
@@ -49,8 +51,8 @@ impl PosDataService for PosGrpcService {
         &self,
         request: Request<SetConfigRequest>,
     ) -> Result<Response<SetConfigResponse>, Status> {
+        let request = request.into_inner();
         let config = request
-            .into_inner()
             .config
             .ok_or_else(|| Status::invalid_argument("missing config"))?;
 
@@ -59,14 +61,67 @@ impl PosDataService for PosGrpcService {
             .map_err(|e| Status::internal(format!("internal error: {}", e)))?;
 
         server
-            .call(SetConfig(config))
+            .call(SetConfig {
+                config,
+                force: request.force,
+            })
             .await
             .map_err(|e| Status::internal(format!("internal error: {}", e)))?
-            .map_err(|e| Status::internal(format!("internal error: {}", e)))?;
+            .map_err(|e| match e.downcast_ref::<SetConfigError>() {
+                Some(SetConfigError::JobsInProgress(_)) => {
+                    Status::failed_precondition(e.to_string())
+                }
+                Some(SetConfigError::InvalidConfig(_)) => Status::invalid_argument(e.to_string()),
+                None => Status::internal(format!("internal error: {}", e)),
+            })?;
 
         Ok(Response::new(SetConfigResponse {}))
     }
 
+    async fn enable_provider(
+        &self,
+        request: Request<EnableProviderRequest>,
+    ) -> Result<Response<EnableProviderResponse>, Status> {
+        let filter = request
+            .into_inner()
+            .filter
+            .ok_or_else(|| Status::invalid_argument("missing provider filter"))?;
+
+        let server = PosServer::from_registry()
+            .await
+            .map_err(|e| Status::internal(format!("internal error: {}", e)))?;
+
+        let providers = server
+            .call(EnableProviders(filter))
+            .await
+            .map_err(|e| Status::internal(format!("internal error: {}", e)))?
+            .map_err(|e| Status::internal(format!("internal error: {}", e)))?;
+
+        Ok(Response::new(EnableProviderResponse { providers }))
+    }
+
+    async fn disable_provider(
+        &self,
+        request: Request<DisableProviderRequest>,
+    ) -> Result<Response<DisableProviderResponse>, Status> {
+        let filter = request
+            .into_inner()
+            .filter
+            .ok_or_else(|| Status::invalid_argument("missing provider filter"))?;
+
+        let server = PosServer::from_registry()
+            .await
+            .map_err(|e| Status::internal(format!("internal error: {}", e)))?;
+
+        let providers = server
+            .call(DisableProviders(filter))
+            .await
+            .map_err(|e| Status::internal(format!("internal error: {}", e)))?
+            .map_err(|e| Status::internal(format!("internal error: {}", e)))?;
+
+        Ok(Response::new(DisableProviderResponse { providers }))
+    }
+
     async fn get_config(
         &self,
         _request: Request<GetConfigRequest>,
@@ -100,7 +155,16 @@ impl PosDataService for PosGrpcService {
             .call(AddJob(add_job_request))
             .await
             .map_err(|e| Status::internal(format!("internal error: {}", e)))?
-            .map_err(|e| Status::internal(format!("internal error: {}", e)))?;
+            .map_err(|e| match e.downcast_ref::<AddJobError>() {
+                Some(AddJobError::InvalidParams(_)) | Some(AddJobError::InvalidJob(_)) => {
+                    Status::invalid_argument(e.to_string())
+                }
+                Some(AddJobError::FailedPrecondition(_)) => {
+                    Status::failed_precondition(e.to_string())
+                }
+                Some(AddJobError::Unavailable(_)) => Status::unavailable(e.to_string()),
+                None => Status::internal(format!("internal error: {}", e)),
+            })?;
 
         Ok(Response::new(AddJobResponse { job: Some(job) }))
     }
@@ -158,6 +222,27 @@ impl PosDataService for PosGrpcService {
         Ok(Response::new(AbortJobResponse {}))
     }
 
+    async fn move_job(
+        &self,
+        request: Request<MoveJobRequest>,
+    ) -> Result<Response<MoveJobResponse>, Status> {
+        let req = request.into_inner();
+        let server = PosServer::from_registry()
+            .await
+            .map_err(|e| Status::internal(format!("internal error: {}", e)))?;
+
+        let jobs = server
+            .call(MoveJob {
+                id: req.id,
+                position: req.position,
+            })
+            .await
+            .map_err(|e| Status::internal(format!("internal error: {}", e)))?
+            .map_err(|e| Status::invalid_argument(format!("can't move job: {}", e)))?;
+
+        Ok(Response::new(MoveJobResponse { jobs }))
+    }
+
     type SubscribeJobStatusStreamStream = ReceiverStream<Result<JobStatusStreamResponse, Status>>;
 
     async fn subscribe_job_status_stream(
@@ -0,0 +1,136 @@
+use anyhow::{bail, Result};
+use pos_api::api::{Config, Job, JobStripe};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A job checkpoint is stored in a sidecar file next to the job's pos data files.
+/// The indexes of each stripe before the stripe's next_index are durably stored in the data files.
+/// The checkpoint includes the compute params and data files layout used to create the data so the job
+/// can only be continued with the same params.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct Checkpoint {
+    pub(crate) job_id: u64,
+    pub(crate) stripes: Vec<CheckpointStripe>,
+    pub(crate) size_bits: u64,
+    pub(crate) client_id: String,
+    pub(crate) salt: String,
+    pub(crate) n: u32,
+    pub(crate) r: u32,
+    pub(crate) p: u32,
+    pub(crate) bits_per_index: u32,
+    pub(crate) max_file_size: u64,
+    pub(crate) compute_pow_solution: bool,
+    pub(crate) pow_difficulty: String,
+    pub(crate) pow_solutions: Vec<u64>,
+}
+
+/// A job stripe's durable progress
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct CheckpointStripe {
+    pub(crate) start_index: u64,
+    pub(crate) end_index: u64, // inclusive
+    pub(crate) next_index: u64,
+}
+
+impl Checkpoint {
+    /// Create a checkpoint for a job's data computed with config params.
+    /// durable has the next durably stored index of each of the job's stripes.
+    pub(crate) fn new(job: &Job, config: &Config, durable: &[u64]) -> Checkpoint {
+        Checkpoint {
+            job_id: job.id,
+            stripes: job
+                .stripes
+                .iter()
+                .zip(durable)
+                .map(|(s, next_index)| CheckpointStripe {
+                    start_index: s.start_index,
+                    end_index: s.end_index,
+                    next_index: *next_index,
+                })
+                .collect(),
+            size_bits: job.size_bits,
+            client_id: hex::encode(&job.client_id),
+            salt: hex::encode(&config.salt),
+            n: config.n,
+            r: config.r,
+            p: config.p,
+            bits_per_index: config.bits_per_index,
+            max_file_size: config.max_file_size,
+            compute_pow_solution: job.compute_pow_solution,
+            pow_difficulty: hex::encode(&job.pow_difficulty),
+            pow_solutions: job.pow_solutions.clone(),
+        }
+    }
+
+    /// Returns the path of a job's checkpoint file in a data dir
+    pub(crate) fn path(data_dir: &str, job_id: u64) -> PathBuf {
+        Path::new(data_dir).join(format!("{}.checkpoint", job_id))
+    }
+
+    /// Durably write the checkpoint. The checkpoint is written to a temp file which replaces
+    /// the checkpoint file so a crash never leaves a partially written checkpoint.
+    pub(crate) fn save(&self, path: &Path) -> Result<()> {
+        let tmp_path = path.with_extension("checkpoint.tmp");
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(serde_json::to_string_pretty(self)?.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    pub(crate) fn load(path: &Path) -> Result<Checkpoint> {
+        let data = fs::read(path)?;
+        Ok(serde_json::from_slice(&data)?)
+    }
+
+    /// Delete a checkpoint file. Deleting a missing checkpoint is not an error.
+    pub(crate) fn delete(path: &Path) -> Result<()> {
+        match fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Returns the checkpoint's stripes of a job
+    pub(crate) fn job_stripes(&self) -> Vec<JobStripe> {
+        self.stripes
+            .iter()
+            .map(|s| JobStripe {
+                start_index: s.start_index,
+                end_index: s.end_index,
+                next_index: s.next_index,
+                compute_provider_id: u32::MAX,
+                last_error: None,
+            })
+            .collect()
+    }
+
+    /// Verify that the checkpoint belongs to job and that its data was computed using config
+    pub(crate) fn verify(&self, job: &Job, config: &Config) -> Result<()> {
+        if self.job_id != job.id
+            || self.size_bits != job.size_bits
+            || self.client_id != hex::encode(&job.client_id)
+        {
+            bail!("checkpoint doesn't match job {}", job.id)
+        }
+
+        let expected = Checkpoint::new(job, config, &[]);
+        if self.salt != expected.salt
+            || self.n != expected.n
+            || self.r != expected.r
+            || self.p != expected.p
+            || self.bits_per_index != expected.bits_per_index
+            || self.max_file_size != expected.max_file_size
+        {
+            bail!(
+                "job {} data was computed with different compute params than the current config",
+                job.id
+            )
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use anyhow::{bail, Result};
+use pos_compute::{
+    ComputeBackend, ComputeError, FfiBackend, MockBackend, MockProvider, ScryptBackend,
+    COMPUTE_API_CLASS_CPU, COMPUTE_API_CLASS_CUDA, COMPUTE_API_CLASS_VULKAN,
+};
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A mock compute provider of the mock backend. A provider's id is its index in the config
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub(crate) struct MockProviderConfig {
+    pub(crate) model: String,
+    /// provider class: cpu, cuda or vulkan
+    pub(crate) class: String,
+    /// simulated hash rate. Computations complete without a delay when 0
+    pub(crate) hashes_per_sec: u64,
+    /// number of the provider's first computations which fail. The provider always fails when u32::MAX
+    pub(crate) failures: u32,
+    /// the provider fails self tests
+    pub(crate) fails_self_test: bool,
+}
+
+impl Default for MockProviderConfig {
+    fn default() -> Self {
+        MockProviderConfig {
+            model: "mock provider".to_string(),
+            class: "cpu".to_string(),
+            hashes_per_sec: 0,
+            failures: 0,
+            fails_self_test: false,
+        }
+    }
+}
+
+/// Returns a compute backend by its config name: gpu-setup, scrypt or mock.
+/// The gpu-setup lib is loaded from gpu_setup_lib or from its default path when empty. When the lib can't be
+/// loaded and gpu_setup_fallback is set, the scrypt backend is returned instead of an error.
+/// The scrypt backend computes labels with all cpu cores without the gpu-setup lib.
+/// The mock backend's providers are the config's mock providers
+pub(crate) fn new_backend(
+    name: &str,
+    gpu_setup_lib: &str,
+    gpu_setup_fallback: bool,
+    mock_providers: Vec<MockProviderConfig>,
+) -> Result<Arc<dyn ComputeBackend>> {
+    match name.trim().to_lowercase().as_str() {
+        "gpu-setup" => {
+            let path = match gpu_setup_lib.trim() {
+                "" => None,
+                path => Some(Path::new(path)),
+            };
+            match FfiBackend::load(path) {
+                Ok(backend) => {
+                    info!("loaded gpu-setup lib {}", backend.lib().path().display());
+                    Ok(Arc::new(backend))
+                }
+                Err(e) if gpu_setup_fallback => {
+                    warn!("{}. falling back to the scrypt cpu backend", e);
+                    Ok(Arc::new(ScryptBackend::default()))
+                }
+                Err(e) => bail!(e),
+            }
+        }
+        "scrypt" => Ok(Arc::new(ScryptBackend::default())),
+        "mock" => {
+            if mock_providers.is_empty() {
+                bail!("the mock compute backend requires at least one mock provider")
+            }
+            let mut providers = vec![];
+            for p in mock_providers.iter() {
+                let compute_api = match p.class.to_lowercase().as_str() {
+                    "cpu" => COMPUTE_API_CLASS_CPU,
+                    "cuda" => COMPUTE_API_CLASS_CUDA,
+                    "vulkan" => COMPUTE_API_CLASS_VULKAN,
+                    _ => bail!("unknown mock provider class: {}", p.class),
+                };
+                providers.push(MockProvider {
+                    model: p.model.clone(),
+                    compute_api,
+                    hashes_per_sec: p.hashes_per_sec,
+                });
+            }
+            let backend = MockBackend::new(providers);
+            for (id, p) in mock_providers.iter().enumerate() {
+                backend.inject_failures(id as u32, ComputeError::Error, p.failures);
+                if p.fails_self_test {
+                    backend.inject_self_test_failure(id as u32);
+                }
+            }
+            Ok(Arc::new(backend))
+        }
+        _ => bail!(
+            "unknown compute backend: {}. expected gpu-setup, scrypt or mock",
+            name
+        ),
+    }
+}
@@ -0,0 +1,414 @@
+use crate::metadata::DataFile;
+use anyhow::{bail, Result};
+use pos_api::api::{Config, Job, JobStripe};
+use std::fs::{File, OpenOptions};
+use std::io::prelude::*;
+use std::io::{BufWriter, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// The pos data of a job is written to a single <job_id>.pos file in the pos data dir unless
+/// config.max_file_size is set. The data is then split into a series of numbered files <job_id>_0.pos,
+/// <job_id>_1.pos, ... Each file holds indexes_per_file consecutive indexes except for the last
+/// file which holds the remaining indexes. Files are at most config.max_file_size bytes.
+#[derive(Debug, Clone)]
+pub(crate) struct DataFiles {
+    paths: Vec<PathBuf>,
+    bits_per_index: u64,
+    indexes: u64,
+    indexes_per_file: u64,
+}
+
+impl DataFiles {
+    /// Returns the data files layout of a job's data computed with config params
+    pub(crate) fn new(job: &Job, config: &Config) -> Result<DataFiles> {
+        let bits_per_index = config.bits_per_index as u64;
+        if bits_per_index == 0 {
+            bail!("bits per index must be positive")
+        }
+
+        let indexes = job.size_bits / bits_per_index;
+        if indexes == 0 {
+            bail!("job {} has no indexes", job.id)
+        }
+
+        let indexes_per_file = match config.max_file_size {
+            0 => indexes,
+            max_file_size => {
+                // a multiple of 8 indexes so that files always end on a byte boundary
+                let indexes_per_file = max_file_size.saturating_mul(8) / bits_per_index / 8 * 8;
+                if indexes_per_file == 0 {
+                    bail!(
+                        "max file size {} bytes is too small for {} bits per index",
+                        max_file_size,
+                        bits_per_index
+                    )
+                }
+                indexes_per_file.min(indexes)
+            }
+        };
+
+        let files_count = (indexes - 1) / indexes_per_file + 1;
+
+        let data_dir = Path::new(config.data_dir.as_str());
+        let paths = match config.max_file_size {
+            0 => vec![data_dir.join(job.file_name())],
+            _ => (0..files_count)
+                .map(|n| data_dir.join(job.data_file_name(n)))
+                .collect(),
+        };
+
+        Ok(DataFiles {
+            paths,
+            bits_per_index,
+            indexes,
+            indexes_per_file,
+        })
+    }
+
+    /// Returns the first and last (inclusive) index of the nth file
+    fn file_indexes(&self, n: usize) -> (u64, u64) {
+        let start = n as u64 * self.indexes_per_file;
+        let end = (start + self.indexes_per_file).min(self.indexes) - 1;
+        (start, end)
+    }
+
+    /// Returns the nth file's offset in bytes from the start of the job's data
+    fn file_offset(&self, n: usize) -> u64 {
+        n as u64 * self.indexes_per_file * self.bits_per_index / 8
+    }
+
+    /// Returns the nth file's size in bytes when all its indexes are written
+    fn file_size(&self, n: usize) -> u64 {
+        let (start, end) = self.file_indexes(n);
+        (end + 1 - start) * self.bits_per_index / 8
+    }
+
+    /// Returns the size in bytes of the nth file when bytes_written bytes of the job's data are written
+    fn file_size_at(&self, n: usize, bytes_written: u64) -> u64 {
+        bytes_written
+            .saturating_sub(self.file_offset(n))
+            .min(self.file_size(n))
+    }
+
+    /// Returns the manifest of the job's data files which maps index ranges to files
+    pub(crate) fn manifest(&self) -> Vec<DataFile> {
+        self.paths
+            .iter()
+            .enumerate()
+            .map(|(n, path)| {
+                let (start_index, end_index) = self.file_indexes(n);
+                DataFile {
+                    name: path.file_name().unwrap().to_string_lossy().to_string(),
+                    start_index,
+                    end_index,
+                    size_bytes: self.file_size(n),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the length in bytes of the nth file when the indexes of all stripes up to their
+    /// next index are written
+    fn written_len(&self, n: usize, stripes: &[JobStripe]) -> u64 {
+        let file_end = self.file_offset(n) + self.file_size(n);
+        stripes
+            .iter()
+            .filter(|s| s.start_index * self.bits_per_index / 8 < file_end)
+            .map(|s| self.file_size_at(n, s.next_index * self.bits_per_index / 8))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the length in bytes of a data file. A missing file has no data.
+    fn file_len(path: &Path) -> Result<u64> {
+        match path.metadata() {
+            Ok(metadata) => Ok(metadata.len()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+            Err(e) => bail!("can't access pos data file {}: {}", path.display(), e),
+        }
+    }
+
+    /// Check that the job's data files hold exactly the written indexes of the job's stripes.
+    /// The files are not modified.
+    pub(crate) fn check(&self, stripes: &[JobStripe]) -> Result<()> {
+        for (n, path) in self.paths.iter().enumerate() {
+            let written_len = self.written_len(n, stripes);
+            let len = DataFiles::file_len(path)?;
+            if len != written_len {
+                bail!(
+                    "pos data file {} size is {} bytes. Expected {} bytes",
+                    path.display(),
+                    len,
+                    written_len
+                )
+            }
+        }
+        Ok(())
+    }
+
+    /// Recover the job's data files from their checkpointed stripes. Each file must hold the durably
+    /// computed indexes of all stripes and it is truncated to the end of these indexes, so data which
+    /// was written after the last checkpoint by a stripe that was interrupted is discarded.
+    pub(crate) fn recover(&self, stripes: &[JobStripe]) -> Result<()> {
+        for (n, path) in self.paths.iter().enumerate() {
+            // the file's durable length ends at the last durably computed index in the file
+            let durable_len = self.written_len(n, stripes);
+            let len = DataFiles::file_len(path)?;
+
+            if len < durable_len {
+                bail!(
+                    "pos data file {} is too short: {} bytes. Expected at least {} bytes",
+                    path.display(),
+                    len,
+                    durable_len
+                )
+            }
+
+            if len > durable_len {
+                info!(
+                    "truncating pos data file {} from {} to {} bytes",
+                    path.display(),
+                    len,
+                    durable_len
+                );
+                let file = DataFiles::open_file(path)?;
+                file.set_len(durable_len)?;
+                file.sync_all()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete the job's data files. Errors are logged.
+    pub(crate) fn delete(&self) {
+        for path in self.paths.iter() {
+            match fs::remove_file(path) {
+                Ok(()) => info!("deleted pos data file {}", path.display()),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => info!("failed to delete {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    /// Open the job's data files for writing from index. Files are created as needed and
+    /// existing data is overwritten so each stripe of a job can write its own region of the files.
+    pub(crate) fn open_writer(self, index: u64) -> Result<DataWriter> {
+        let offset = index * self.bits_per_index / 8;
+
+        // the file which holds the index, or the last file when the index is out of range
+        let file_index = (0..self.paths.len())
+            .find(|n| offset < self.file_offset(*n) + self.file_size(*n))
+            .unwrap_or(self.paths.len() - 1);
+
+        let file_pos = self.file_size_at(file_index, offset);
+        let mut file = DataFiles::open_file(&self.paths[file_index])?;
+        file.seek(SeekFrom::Start(file_pos))?;
+
+        Ok(DataWriter {
+            bytes_left: self.file_size(file_index) - file_pos,
+            writer: BufWriter::new(file),
+            file_index,
+            files: self,
+        })
+    }
+
+    /// Open a data file for writing without truncating its existing data
+    fn open_file(path: &Path) -> io::Result<File> {
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+    }
+}
+
+/// A writer of a region of a job's pos data which moves to the next data file when a file is full.
+/// Full files are synced to disk before the next file is opened.
+pub(crate) struct DataWriter {
+    files: DataFiles,
+    file_index: usize,
+    bytes_left: u64, // bytes left to write to the current file
+    writer: BufWriter<File>,
+}
+
+impl DataWriter {
+    /// Returns the path of the file which is currently written
+    pub(crate) fn path(&self) -> &Path {
+        &self.files.paths[self.file_index]
+    }
+
+    /// Flush and sync written data to disk
+    pub(crate) fn sync(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+        self.writer.get_ref().sync_data()
+    }
+}
+
+impl Write for DataWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        if self.bytes_left == 0 {
+            if self.file_index + 1 == self.files.paths.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "data is out of the job's data files range",
+                ));
+            }
+
+            self.sync()?;
+            self.file_index += 1;
+            self.bytes_left = self.files.file_size(self.file_index);
+            self.writer = BufWriter::new(DataFiles::open_file(self.path())?);
+        }
+
+        let len = (buf.len() as u64).min(self.bytes_left) as usize;
+        let written = self.writer.write(&buf[..len])?;
+        self.bytes_left -= written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn job(size_bits: u64) -> Job {
+        Job {
+            id: 42,
+            size_bits,
+            ..Job::default()
+        }
+    }
+
+    fn config(max_file_size: u64, data_dir: &str) -> Config {
+        Config {
+            data_dir: data_dir.to_string(),
+            bits_per_index: 8,
+            max_file_size,
+            ..Config::default()
+        }
+    }
+
+    fn stripe(start_index: u64, end_index: u64, next_index: u64) -> JobStripe {
+        JobStripe {
+            start_index,
+            end_index,
+            next_index,
+            ..JobStripe::default()
+        }
+    }
+
+    #[test]
+    fn single_file() {
+        let files = DataFiles::new(&job(8 * 1000), &config(0, "data")).unwrap();
+        let manifest = files.manifest();
+        assert_eq!(manifest.len(), 1);
+        assert_eq!(manifest[0].name, "42.pos");
+        assert_eq!((manifest[0].start_index, manifest[0].end_index), (0, 999));
+        assert_eq!(manifest[0].size_bytes, 1000);
+        assert_eq!(files.paths[0], Path::new("data").join("42.pos"));
+    }
+
+    #[test]
+    fn split_files() {
+        // 100 bytes files hold 96 indexes as files hold a multiple of 8 indexes
+        let files = DataFiles::new(&job(8 * 1000), &config(100, "data")).unwrap();
+        assert_eq!(files.indexes_per_file, 96);
+
+        let manifest = files.manifest();
+        assert_eq!(manifest.len(), 11);
+        for (n, file) in manifest.iter().enumerate() {
+            assert_eq!(file.name, format!("42_{}.pos", n));
+            assert_eq!(file.start_index, n as u64 * 96);
+        }
+        assert_eq!(manifest[0].end_index, 95);
+        assert_eq!(manifest[0].size_bytes, 96);
+
+        // the last file holds the remaining indexes
+        assert_eq!(
+            (manifest[10].start_index, manifest[10].end_index),
+            (960, 999)
+        );
+        assert_eq!(manifest[10].size_bytes, 40);
+        let total: u64 = manifest.iter().map(|f| f.size_bytes).sum();
+        assert_eq!(total, 1000);
+    }
+
+    #[test]
+    fn split_file_bigger_than_job() {
+        let files = DataFiles::new(&job(8 * 1000), &config(1 << 20, "data")).unwrap();
+        let manifest = files.manifest();
+        assert_eq!(manifest.len(), 1);
+        assert_eq!(manifest[0].name, "42_0.pos");
+        assert_eq!(manifest[0].size_bytes, 1000);
+    }
+
+    #[test]
+    fn max_file_size_too_small() {
+        // a file must hold at least 8 indexes
+        assert!(DataFiles::new(&job(8 * 1000), &config(7, "data")).is_err());
+        assert!(DataFiles::new(&job(8 * 1000), &config(8, "data")).is_ok());
+    }
+
+    #[test]
+    fn file_size_at() {
+        let files = DataFiles::new(&job(8 * 1000), &config(100, "data")).unwrap();
+        assert_eq!(files.file_offset(2), 192);
+        assert_eq!(files.file_size_at(0, 50), 50);
+        assert_eq!(files.file_size_at(0, 500), 96);
+        assert_eq!(files.file_size_at(2, 100), 0);
+        assert_eq!(files.file_size_at(2, 200), 8);
+        assert_eq!(files.file_size_at(10, 1000), 40);
+    }
+
+    #[test]
+    fn recover_truncates_files() {
+        let data_dir = env::temp_dir().join(format!("pos_data_files_{}", rand::random::<u64>()));
+        fs::create_dir_all(&data_dir).unwrap();
+        let files = DataFiles::new(&job(8 * 192), &config(96, data_dir.to_str().unwrap())).unwrap();
+
+        // two stripes wrote past their checkpoints
+        fs::write(&files.paths[0], vec![1; 96]).unwrap();
+        fs::write(&files.paths[1], vec![1; 80]).unwrap();
+        let stripes = [stripe(0, 95, 40), stripe(96, 191, 160)];
+        files.recover(&stripes).unwrap();
+        assert_eq!(fs::metadata(&files.paths[0]).unwrap().len(), 40);
+        assert_eq!(fs::metadata(&files.paths[1]).unwrap().len(), 64);
+
+        // a file which is shorter than its checkpoint can't be recovered
+        let stripes = [stripe(0, 95, 40), stripe(96, 191, 190)];
+        assert!(files.recover(&stripes).is_err());
+
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[test]
+    fn check_files_length() {
+        let data_dir = env::temp_dir().join(format!("pos_data_files_{}", rand::random::<u64>()));
+        fs::create_dir_all(&data_dir).unwrap();
+        let files = DataFiles::new(&job(8 * 1000), &config(0, data_dir.to_str().unwrap())).unwrap();
+
+        fs::write(&files.paths[0], vec![1; 400]).unwrap();
+        assert!(files.check(&[stripe(0, 999, 400)]).is_ok());
+        assert!(files.check(&[stripe(0, 999, 300)]).is_err());
+        assert!(files.check(&[stripe(0, 999, 500)]).is_err());
+
+        // a file with more data than expected isn't truncated
+        assert_eq!(fs::metadata(&files.paths[0]).unwrap().len(), 400);
+
+        // no data file is expected before any index is written
+        fs::remove_file(&files.paths[0]).unwrap();
+        assert!(files.check(&[stripe(0, 999, 0)]).is_ok());
+
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::{anyhow, Result};
+use pos_api::api::Job;
+use prost::Message;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// JobStore is an on-disk store of the server's jobs.
+/// Each job is stored as a protobuf encoded file named <job_id>.job in the store directory.
+/// Jobs are written to a temp file which is renamed over the job's file so a crash while
+/// saving a job never leaves a partially written job file.
+pub(crate) struct JobStore {
+    dir: PathBuf,
+}
+
+impl JobStore {
+    /// Open a store at dir. The directory is created if it doesn't exist.
+    pub(crate) fn open(dir: &Path) -> Result<JobStore> {
+        fs::create_dir_all(dir)
+            .map_err(|e| anyhow!("can't create jobs store at {}: {}", dir.display(), e))?;
+        Ok(JobStore {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    fn job_path(&self, id: u64) -> PathBuf {
+        self.dir.join(format!("{}.job", id))
+    }
+
+    /// Save a new job or update a stored job
+    pub(crate) fn save(&self, job: &Job) -> Result<()> {
+        let mut buf = Vec::with_capacity(job.encoded_len());
+        job.encode(&mut buf)?;
+
+        let path = self.job_path(job.id);
+        let tmp_path = path.with_extension("job.tmp");
+        fs::write(&tmp_path, &buf)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// Delete a stored job. Deleting a job which is not in the store is not an error.
+    pub(crate) fn delete(&self, id: u64) -> Result<()> {
+        match fs::remove_file(self.job_path(id)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Load all stored jobs ordered by submission time
+    pub(crate) fn load_all(&self) -> Result<Vec<Job>> {
+        let mut jobs = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("job") {
+                continue;
+            }
+
+            let data = fs::read(&path)?;
+            match Job::decode(data.as_slice()) {
+                Ok(job) => jobs.push(job),
+                Err(e) => error!("skipping invalid job file {}: {}", path.display(), e),
+            }
+        }
+
+        jobs.sort_by_key(|j| j.submitted);
+        Ok(jobs)
+    }
+}
@@ -5,18 +5,32 @@ extern crate pos_api;
 extern crate pos_compute;
 
 mod api;
+mod checkpoint;
+mod compute_backend;
+mod data_files;
+mod job_store;
+mod metadata;
 mod pos_task;
+mod provider_policy;
+mod scheduler;
 mod server;
 
-use crate::server::{Init, PosServer, SetConfig, StartGrpcService};
+use crate::compute_backend::{new_backend, MockProviderConfig};
+use crate::provider_policy::{ProviderClassPolicy, ProviderPolicies};
+use crate::scheduler::new_scheduler;
+use crate::server::{Init, PosServer, ResumeJobs, SetConfig, Shutdown, StartGrpcService};
 use chrono::prelude::*;
 use clap::{App, Arg};
 use config::Config;
 use env_logger::fmt::Color;
 use env_logger::Builder;
 use log::*;
+use pos_api::api::ProviderFilter;
+use pos_compute::ComputeBackend;
+use std::collections::HashMap;
 use std::env;
 use std::io::Write;
+use std::sync::Arc;
 use tokio::signal;
 use xactor::*;
 
@@ -24,6 +38,12 @@ const DEFAULT_GRPC_PORT: u32 = 6667;
 const DEFAULT_HOST: &str = "[::1]";
 const DEFAULT_INDEXES_PER_CYCLE: u64 = 1024; // 9 * 128 * 1024;
 const DEFAULT_BITS_PER_INDEX: u32 = 8;
+const DEFAULT_CHECKPOINT_INTERVAL: u32 = 16;
+const DEFAULT_BENCHMARK_INDEXES: u64 = 0; // providers are only benchmarked when configured as it delays startup
+const DEFAULT_POW_SEARCH_FACTOR: u32 = 20; // chance of missing an existing solution is about e^-20
+const DEFAULT_COMPUTE_RETRIES: u32 = 3;
+const DEFAULT_RETRY_BACKOFF_MS: u32 = 500;
+const DEFAULT_QUARANTINE_FAILURES: u32 = 3;
 const DEFAULT_SALT: &str = "114a00005de29b0aaad6814e5f33d357686da48923e8e4864ee5d6e20053e886";
 
 // "0000ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
@@ -45,6 +65,13 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
                 .help("provide server configuration file")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("gpu-setup-lib")
+                .long("gpu-setup-lib")
+                .takes_value(true)
+                .value_name("FILE")
+                .help("gpu-setup lib path. overrides the gpu_setup_lib config param"),
+        )
         .get_matches();
 
     if let Some(conf_file) = args.value_of("config") {
@@ -52,8 +79,19 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
             .merge(config::File::with_name(conf_file).required(false))
             .unwrap();
     }
+    if let Some(lib) = args.value_of("gpu-setup-lib") {
+        config.set("gpu_setup_lib", lib).unwrap();
+    }
 
-    start_server(config).await?;
+    let backend = new_backend(
+        &config.get_str("compute_backend").unwrap(),
+        &config.get_str("gpu_setup_lib").unwrap(),
+        config.get_bool("gpu_setup_fallback").unwrap(),
+        config.get::<Vec<MockProviderConfig>>("mock_providers")?,
+    )?;
+    // the compute lib logs when the server logs debug messages
+    backend.set_logging(log::max_level() >= LevelFilter::Debug);
+    start_server(config, backend.clone()).await?;
 
     // block app until it is terminated
     signal::ctrl_c()
@@ -62,31 +100,80 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
 
     info!("got signal - terminating app");
 
+    // the compute backend is shut down once no job is computed with it
+    let tasks = PosServer::from_registry().await?.call(Shutdown).await??;
+    for task in tasks {
+        if let Err(e) = task.await {
+            error!("job task failed: {}", e);
+        }
+    }
+
+    if let Err(e) = backend.shutdown() {
+        error!("failed to shutdown compute backend: {}", e);
+    }
+
     Ok(())
 }
 
-async fn start_server(config: Config) -> Result<()> {
+async fn start_server(config: Config, backend: Arc<dyn ComputeBackend>) -> Result<()> {
     // init the server (one-time per process, pre config)
     let use_cpu_provider = config.get_bool("use_cpu_provider").unwrap();
+    let use_all_providers = config.get_bool("use_all_providers").unwrap();
+    let policies = ProviderPolicies::new(
+        config.get::<HashMap<String, ProviderClassPolicy>>("provider_policies")?,
+    )?;
+    let jobs_store_dir = config.get_str("jobs_store_dir").unwrap();
+    let enabled_providers = parse_provider_filters(&config.get_str("enabled_providers").unwrap())?;
+    let disabled_providers =
+        parse_provider_filters(&config.get_str("disabled_providers").unwrap())?;
+    let scheduler = new_scheduler(&config.get_str("scheduler").unwrap())?;
+    let benchmark_indexes = config.get_int("benchmark_indexes").unwrap() as u64;
+    let self_test_providers = config.get_bool("self_test_providers").unwrap();
     let server = PosServer::from_registry().await?;
-    server.call(Init { use_cpu_provider }).await??;
+    server
+        .call(Init {
+            backend,
+            use_cpu_provider,
+            use_all_providers,
+            policies,
+            scheduler,
+            jobs_store_dir,
+            enabled_providers,
+            disabled_providers,
+            benchmark_indexes,
+            self_test_providers,
+        })
+        .await??;
 
     // set server config
     let salt = hex::decode(config.get_str("salt").unwrap()).unwrap();
     use pos_api::api::Config;
     server
-        .call(SetConfig(Config {
-            // default config
-            data_dir: config.get_str("data_dir").unwrap(),
-            indexes_per_compute_cycle: config.get_int("indexes_per_cycle").unwrap() as u64,
-            bits_per_index: config.get_int("bits_per_index").unwrap() as u32,
-            salt,
-            n: config.get_int("n").unwrap() as u32,
-            r: config.get_int("r").unwrap() as u32,
-            p: config.get_int("p").unwrap() as u32,
-        }))
+        .call(SetConfig {
+            force: false,
+            config: Config {
+                // default config
+                data_dir: config.get_str("data_dir").unwrap(),
+                indexes_per_compute_cycle: config.get_int("indexes_per_cycle").unwrap() as u64,
+                bits_per_index: config.get_int("bits_per_index").unwrap() as u32,
+                salt,
+                n: config.get_int("n").unwrap() as u32,
+                r: config.get_int("r").unwrap() as u32,
+                p: config.get_int("p").unwrap() as u32,
+                checkpoint_interval: config.get_int("checkpoint_interval").unwrap() as u32,
+                max_file_size: config.get_int("max_file_size").unwrap() as u64,
+                pow_search_factor: config.get_int("pow_search_factor").unwrap() as u32,
+                pow_search_timeout: config.get_int("pow_search_timeout").unwrap() as u64,
+                compute_retries: config.get_int("compute_retries").unwrap() as u32,
+                retry_backoff_ms: config.get_int("retry_backoff_ms").unwrap() as u32,
+                quarantine_failures: config.get_int("quarantine_failures").unwrap() as u32,
+            },
+        })
         .await??;
 
+    // load and resume the stored jobs which were queued or interrupted before the server was restarted
+    server.call(ResumeJobs).await??;
+
     info!("server starting...");
 
     server
@@ -103,6 +190,15 @@ async fn start_server(config: Config) -> Result<()> {
     Ok(())
 }
 
+/// Parse a comma separated list of provider filters such as "class:cuda, model:*intel*"
+fn parse_provider_filters(filters: &str) -> Result<Vec<ProviderFilter>> {
+    filters
+        .split(',')
+        .filter(|f| !f.trim().is_empty())
+        .map(ProviderFilter::parse)
+        .collect()
+}
+
 fn init_logging() {
     let mut builder = Builder::new();
 
@@ -168,7 +264,53 @@ fn get_default_config() -> config::Config {
         .unwrap()
         .set_default("p", 1.to_string())
         .unwrap()
+        .set_default(
+            "checkpoint_interval",
+            DEFAULT_CHECKPOINT_INTERVAL.to_string(),
+        )
+        .unwrap()
+        .set_default("max_file_size", 0.to_string())
+        .unwrap()
+        .set_default("pow_search_factor", DEFAULT_POW_SEARCH_FACTOR.to_string())
+        .unwrap()
+        .set_default("pow_search_timeout", 0.to_string())
+        .unwrap()
+        .set_default("compute_retries", DEFAULT_COMPUTE_RETRIES.to_string())
+        .unwrap()
+        .set_default("retry_backoff_ms", DEFAULT_RETRY_BACKOFF_MS.to_string())
+        .unwrap()
+        .set_default(
+            "quarantine_failures",
+            DEFAULT_QUARANTINE_FAILURES.to_string(),
+        )
+        .unwrap()
         .set_default("use_cpu_provider", true.to_string())
         .unwrap()
+        .set_default("use_all_providers", false.to_string())
+        .unwrap()
+        .set_default("provider_policies", HashMap::<String, String>::new())
+        .unwrap()
+        .set_default("scheduler", "fifo")
+        .unwrap()
+        .set_default("compute_backend", "gpu-setup")
+        .unwrap()
+        // loaded from the GPU_SETUP_LIB env var path, the executable's dir or the system's libs path when empty
+        .set_default("gpu_setup_lib", "")
+        .unwrap()
+        .set_default("gpu_setup_fallback", true.to_string())
+        .unwrap()
+        .set_default("mock_providers", Vec::<String>::new())
+        .unwrap()
+        .set_default("benchmark_indexes", DEFAULT_BENCHMARK_INDEXES.to_string())
+        .unwrap()
+        .set_default("self_test_providers", false.to_string())
+        .unwrap()
+        // an empty jobs store dir disables persisting jobs
+        .set_default("jobs_store_dir", "./jobs")
+        .unwrap()
+        .set_default("enabled_providers", "")
+        .unwrap()
+        .set_default("disabled_providers", "")
+        .unwrap()
         .clone()
 }
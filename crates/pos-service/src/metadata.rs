@@ -0,0 +1,94 @@
+use crate::data_files::DataFiles;
+use anyhow::Result;
+use pos_api::api::job::JobStatus;
+use pos_api::api::{Config, Job};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A pos data file of a job in the job's data files manifest
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct DataFile {
+    pub(crate) name: String,
+    pub(crate) start_index: u64, // first index in the file
+    pub(crate) end_index: u64,   // last index in the file (inclusive)
+    pub(crate) size_bytes: u64,  // file size when the job is completed
+}
+
+/// Job metadata is written to <job_id>.data.json in the pos data folder when a job starts,
+/// as its data is synced to disk and when it stops. It allows tools to use a job's pos data
+/// without querying the server.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub(crate) struct JobMetadata {
+    pub(crate) job_id: u64,
+    pub(crate) friendly_name: String,
+    pub(crate) status: String,
+    pub(crate) client_id: String,
+    pub(crate) salt: String,
+    pub(crate) n: u32,
+    pub(crate) r: u32,
+    pub(crate) p: u32,
+    pub(crate) bits_per_index: u32,
+    pub(crate) start_index: u64,
+    pub(crate) end_index: u64, // inclusive
+    pub(crate) size_bits: u64,
+    pub(crate) bits_written: u64,
+    pub(crate) files: Vec<DataFile>,
+    pub(crate) compute_pow_solution: bool,
+    pub(crate) pow_difficulty: String,
+    pub(crate) pow_solution_index: Option<u64>,
+    pub(crate) pow_solutions: Vec<u64>,
+    pub(crate) server_version: String,
+    pub(crate) library_version: String,
+}
+
+impl JobMetadata {
+    /// Create metadata for a job's data computed with config params
+    pub(crate) fn new(job: &Job, config: &Config) -> Result<JobMetadata> {
+        let files = DataFiles::new(job, config)?;
+        let indexes = job.size_bits / config.bits_per_index as u64;
+        let pow_solution_index = match job.pow_solution_index {
+            u64::MAX => None,
+            idx => Some(idx),
+        };
+
+        Ok(JobMetadata {
+            job_id: job.id,
+            friendly_name: job.friendly_name.clone(),
+            status: JobStatus::try_from(job.status)
+                .map_or("unknown".to_string(), |s| s.to_string()),
+            client_id: hex::encode(&job.client_id),
+            salt: hex::encode(&config.salt),
+            n: config.n,
+            r: config.r,
+            p: config.p,
+            bits_per_index: config.bits_per_index,
+            start_index: 0,
+            end_index: indexes - 1,
+            size_bits: job.size_bits,
+            bits_written: job.bits_written,
+            files: files.manifest(),
+            compute_pow_solution: job.compute_pow_solution,
+            pow_difficulty: hex::encode(&job.pow_difficulty),
+            pow_solution_index,
+            pow_solutions: job.pow_solutions.clone(),
+            server_version: env!("CARGO_PKG_VERSION").to_string(),
+            library_version: pos_compute::VERSION.to_string(),
+        })
+    }
+
+    /// Returns the path of a job's metadata file in a data dir
+    pub(crate) fn path(data_dir: &str, job: &Job) -> PathBuf {
+        Path::new(data_dir).join(job.metadata_file_name())
+    }
+
+    /// Write the metadata to a temp file which replaces the metadata file so readers
+    /// never see a partially written file.
+    pub(crate) fn save(&self, path: &Path) -> Result<()> {
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
@@ -1,35 +1,55 @@
-use crate::server::{PosServer, UpdateJobStatus};
+use crate::checkpoint::Checkpoint;
+use crate::data_files::{DataFiles, DataWriter};
+use crate::metadata::JobMetadata;
+use crate::server::{FailoverProvider, PosServer, UpdateJobStatus};
 use anyhow::{bail, Result};
+use pos_api::api::compute_retry::Action;
 use pos_api::api::job::JobStatus;
-use pos_api::api::{Config, Job, JobError};
-use std::fs::File;
+use pos_api::api::job_error::Error;
+use pos_api::api::{ComputeRetry, Config, Job, JobError, JobStripe};
+use std::collections::HashSet;
 use std::io::prelude::*;
-use std::io::BufWriter;
-use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{self, Duration};
+use tokio::runtime::Handle;
 use tokio::task;
 use xactor::*;
 
-use pos_api::api_extensions::{ComputeOptions, ComputeResults};
-use pos_compute::compute_pos;
-use std::convert::TryFrom;
+use pos_api::api_extensions::ComputeOptions;
+use pos_compute::{ComputeBackend, ComputeError, ComputeOutcome, ComputeRequest, HASH_SIZE};
 
 impl PosServer {
-    /// helper sync function used to update job status via the server service from blocking code
+    /// helper sync function used to update job status via the server service from blocking code.
+    /// Blocks until the server processed the update so job updates are delivered in order.
     fn update_job_status(job: &Job) -> Result<()> {
         let task_job = job.clone();
-        tokio::spawn(async move {
+        Handle::current().block_on(async move {
             PosServer::from_registry()
-                .await
-                .expect("failed to get server service from registry")
+                .await?
                 .call(UpdateJobStatus(task_job))
-                .await
-                .expect("failed to call server service")
-                .expect("UpdateJobStatus error");
-        });
-        Ok(())
+                .await?
+        })
+    }
+
+    /// helper sync function used to replace a task's failed provider via the server service from blocking code.
+    /// Returns the provider which continues the failed provider's stripes or None if there is none.
+    fn failover_provider(job_id: u64, provider_id: u32) -> Result<Option<u32>> {
+        Handle::current().block_on(async move {
+            PosServer::from_registry()
+                .await?
+                .call(FailoverProvider {
+                    job_id,
+                    provider_id,
+                })
+                .await?
+        })
     }
 
-    /// Report a task error to the server service
+    /// Stop a task's job due to an error
     fn task_error(job: &mut Job, error: i32, message: String) {
         let err_msg = format!("job {}: {}", job.id, message);
         error!("{}", err_msg);
@@ -39,74 +59,278 @@ impl PosServer {
         });
         job.status = JobStatus::Stopped as i32;
         job.stopped = datetime::Instant::now().seconds() as u64;
-        let _ = PosServer::update_job_status(job);
     }
 
-    /// Find a pow pow solution for a job starting at start_idx.
-    /// Returns the solution index or an error.
+    /// Stop a task's job because it was aborted
+    fn task_canceled(job: &mut Job) {
+        info!("job {} canceled", job.id);
+        job.last_error = Some(JobError {
+            error: Error::Canceled as i32,
+            message: format!("job {}: canceled", job.id),
+        });
+        job.status = JobStatus::Stopped as i32;
+        job.stopped = datetime::Instant::now().seconds() as u64;
+    }
+
+    /// Write a job's metadata file. Metadata is informative so errors are only logged.
+    fn save_metadata(job: &Job, config: &Config) {
+        let path = JobMetadata::path(&config.data_dir, job);
+        if let Err(e) = JobMetadata::new(job, config).and_then(|m| m.save(&path)) {
+            error!("failed to write job metadata {}: {}", path.display(), e);
+        }
+    }
+
+    /// Returns the number of indexes to search for a pow solution of a job with a pow difficulty
+    /// before giving up, or None when the search is not limited.
+    /// A solution's hash must be smaller than the difficulty so the expected number of indexes to search
+    /// is 2^256 / difficulty and the chance of missing an existing solution is about e^-pow_search_factor.
+    fn pow_search_limit(difficulty: &[u8], pow_search_factor: u32) -> Option<u64> {
+        if pow_search_factor == 0 {
+            return None;
+        }
+        let target = difficulty
+            .iter()
+            .fold(0_f64, |acc, byte| acc * 256.0 + *byte as f64);
+        let expected_indexes = 2_f64.powi(8 * difficulty.len() as i32) / target;
+        Some((expected_indexes * pow_search_factor as f64).ceil() as u64)
+    }
+
+    /// Find pow solutions for a job in the indexes range [start_idx, end_idx] until the job has all of its
+    /// wanted solutions. The job's pow solutions, pow_search_index and pow_hashes_searched are updated with
+    /// the search progress. Returns false if the job was canceled while searching or an error when the search
+    /// failed or reached the config's search limits.
     /// A helper function used by the main pos task.
-    fn find_pow_solution(
-        job: &Job,
+    fn find_pow_solutions(
+        job: &mut Job,
         config: &Config,
+        backend: &dyn ComputeBackend,
         start_idx: u64,
-        buffer: &mut Vec<u8>, // caller buffer so no additional allocations are needed
-    ) -> Result<u64> {
-        let mut idx_solution = u64::MAX;
+        end_idx: u64,
+        buffer: &mut [u8], // caller buffer so no additional allocations are needed
+        cancel: &AtomicBool,
+    ) -> Result<bool, JobError> {
+        if job.pow_difficulty.iter().all(|byte| *byte == 0) {
+            return Err(JobError {
+                error: Error::PowSearchLimit as i32,
+                message: "pow difficulty is 0 so no pow solution exists".to_string(),
+            });
+        }
+
+        // searching for all solutions is only limited by the job's indexes range
+        let wanted = job.pow_solutions_wanted();
+        let limit = match job.all_pow_solutions {
+            true => None,
+            false => PosServer::pow_search_limit(&job.pow_difficulty, config.pow_search_factor)
+                .map(|limit| limit.saturating_mul((wanted - job.pow_solutions.len()) as u64)),
+        };
+
+        let max_idx = limit
+            .map_or(u64::MAX, |limit| start_idx.saturating_add(limit))
+            .min(end_idx.saturating_add(1));
+        let started = std::time::Instant::now();
         let mut idx = start_idx;
-        let mut hashes_computed: u64 = 0;
-        let mut hashes_per_sec: u64 = 0;
 
-        while idx_solution == u64::MAX {
-            let end_idx = idx + config.indexes_per_compute_cycle - 1;
+        while job.pow_solutions.len() < wanted {
+            if cancel.load(Ordering::Relaxed) {
+                return Ok(false);
+            }
+
+            if idx >= max_idx {
+                if job.all_pow_solutions {
+                    break;
+                }
+                return Err(JobError {
+                    error: Error::PowSearchLimit as i32,
+                    message: format!(
+                        "found {} of {} pow solutions in {} indexes from index {}",
+                        job.pow_solutions.len(),
+                        wanted,
+                        idx - start_idx,
+                        start_idx
+                    ),
+                });
+            }
+
+            if config.pow_search_timeout > 0
+                && started.elapsed().as_secs() >= config.pow_search_timeout
+            {
+                return Err(JobError {
+                    error: Error::PowSearchLimit as i32,
+                    message: format!(
+                        "found {} pow solutions in {} secs. Searched indexes {} to {}",
+                        job.pow_solutions.len(),
+                        config.pow_search_timeout,
+                        start_idx,
+                        idx - 1
+                    ),
+                });
+            }
+
+            let cycle_end_idx = (idx + config.indexes_per_compute_cycle - 1).min(max_idx - 1);
             info!(
                 "Searching for pow solution at index: {}. {} positions.",
                 idx,
-                end_idx + 1 - idx
+                cycle_end_idx + 1 - idx
             );
 
-            let res = compute_pos(
+            let request = PosServer::compute_request(
+                job,
+                config,
                 job.compute_provider_id,
-                job.client_id.as_ref(),
-                idx,
-                end_idx,
-                config.bits_per_index,
-                config.salt.as_ref(),
+                (idx, cycle_end_idx),
                 ComputeOptions::ComputePow as u32,
-                buffer,
-                config.n,
-                config.r,
-                config.p,
-                job.pow_difficulty.as_ref(),
-                &mut idx_solution as *mut u64,
-                &mut hashes_computed as *mut u64,
-                &mut hashes_per_sec as *mut u64,
             );
+            let search = PosServer::search_pow_range(
+                backend,
+                request,
+                wanted - job.pow_solutions.len(),
+                buffer,
+                cancel,
+            )?;
+            let (solutions, hashes) = match search {
+                Some(search) => search,
+                None => return Ok(false),
+            };
 
-            if res != ComputeResults::NoError as i32
-                && res != ComputeResults::PowSolutionFound as i32
-            {
-                let result = ComputeResults::try_from(res).unwrap();
-                bail!("pow compute error: {}", result);
+            for solution in solutions.iter() {
+                info!("👊 Pow solution found at index: {}", solution);
             }
+            job.add_pow_solutions(&solutions);
+            job.pow_hashes_searched += hashes;
+            idx = cycle_end_idx + 1;
+            job.pow_search_index = idx;
+            let _ = PosServer::update_job_status(job);
+        }
 
-            if res == ComputeResults::PowSolutionFound as i32 {
-                if idx_solution == u64::MAX {
-                    bail!(
-                        "pow compute error. Pow solution found but solution index was not updated"
-                    );
-                }
+        Ok(true)
+    }
+
+    /// Search for up to max_solutions pow solutions of a pow compute request's positions range.
+    /// The compute lib reports the first solution of a range so the search continues after each solution.
+    /// Returns the solutions and the number of hashes computed, or None if the job was canceled while searching.
+    fn search_pow_range(
+        backend: &dyn ComputeBackend,
+        mut request: ComputeRequest,
+        max_solutions: usize,
+        buffer: &mut [u8],
+        cancel: &AtomicBool,
+    ) -> Result<Option<(Vec<u64>, u64)>, JobError> {
+        let (mut idx, end_idx) = (request.start_position, request.end_position);
+        let mut solutions = vec![];
+        let mut hashes: u64 = 0;
+
+        while idx <= end_idx && solutions.len() < max_solutions {
+            request.start_position = idx;
+            let outcome = match backend.compute(&request, buffer) {
+                Ok(outcome) => outcome,
+                Err(ComputeError::Canceled) if cancel.load(Ordering::Relaxed) => return Ok(None),
+                Err(e) => return Err(PosServer::compute_error(&e, "pow compute error")),
+            };
+
+            hashes += outcome.hashes_computed;
+            let idx_solution = match outcome.solution {
+                Some(idx_solution) => idx_solution,
+                None => break,
+            };
+
+            if idx_solution < idx || idx_solution > end_idx {
+                return Err(JobError {
+                    error: Error::GpuComputeError as i32,
+                    message: format!(
+                        "pow compute error. Pow solution found but solution index {} is out of range",
+                        idx_solution
+                    ),
+                });
             }
-            idx += config.indexes_per_compute_cycle;
+            solutions.push(idx_solution);
+            idx = idx_solution + 1;
+        }
+
+        Ok(Some((solutions, hashes)))
+    }
+
+    /// Returns a compute request of a job's indexes range (inclusive) with a provider.
+    /// Shorter client ids are padded with zeros and a job without a pow difficulty has a zero difficulty.
+    fn compute_request(
+        job: &Job,
+        config: &Config,
+        provider_id: u32,
+        range: (u64, u64),
+        options: u32,
+    ) -> ComputeRequest {
+        ComputeRequest {
+            provider_id,
+            id: PosServer::hash_bytes(&job.client_id),
+            start_position: range.0,
+            end_position: range.1,
+            hash_len_bits: config.bits_per_index,
+            salt: PosServer::hash_bytes(&config.salt),
+            options,
+            n: config.n,
+            r: config.r,
+            p: config.p,
+            d: PosServer::hash_bytes(&job.pow_difficulty),
+        }
+    }
+
+    /// Returns bytes padded with zeros to a compute request hash. Validated jobs and configs have no longer hashes
+    fn hash_bytes(bytes: &[u8]) -> [u8; HASH_SIZE] {
+        let mut res = [0_u8; HASH_SIZE];
+        let len = bytes.len().min(HASH_SIZE);
+        res[..len].copy_from_slice(&bytes[..len]);
+        res
+    }
+
+    /// Returns the job error of a failed compute request
+    fn compute_error(e: &ComputeError, message: &str) -> JobError {
+        match e {
+            ComputeError::InvalidRequest(_) => JobError {
+                error: Error::Validation as i32,
+                message: format!("{}: {}", message, e),
+            },
+            _ => JobError::compute(e.code(), message),
+        }
+    }
 
-            // todo: return error result if idx is too large (many iterations have been executed). Figure out what's the probability of this happening.
+    /// Returns a new stripe of the indexes range [start_index, end_index] which is computed up to next_index
+    pub(crate) fn new_stripe(start_index: u64, end_index: u64, next_index: u64) -> JobStripe {
+        JobStripe {
+            start_index,
+            end_index,
+            next_index,
+            compute_provider_id: u32::MAX,
+            last_error: None,
         }
+    }
 
-        Ok(idx_solution)
+    /// Split indexes from start_index to at most count stripes of whole compute cycles.
+    /// The last stripe ends with a partial cycle when indexes is not a multiple of indexes_per_cycle.
+    fn split_stripes(
+        start_index: u64,
+        indexes: u64,
+        indexes_per_cycle: u64,
+        count: u64,
+    ) -> Vec<JobStripe> {
+        let cycles = (indexes + indexes_per_cycle - 1) / indexes_per_cycle;
+        let count = count.min(cycles);
+        let end_index = start_index + indexes - 1;
+        let mut stripes = vec![];
+        let mut start = start_index;
+        for i in 0..count {
+            // the first stripes get the remaining cycles
+            let stripe_cycles = cycles / count + (i < cycles % count) as u64;
+            let end = (start + stripe_cycles * indexes_per_cycle - 1).min(end_index);
+            stripes.push(PosServer::new_stripe(start, end, start));
+            start = end + 1;
+        }
+        stripes
     }
 
-    /// Start a pos data creation task for a pos job
+    /// Start a pos data creation task for a pos job.
+    /// The job's stripes are computed in parallel by all idle providers which may compute it.
     pub(crate) async fn start_task(&mut self, job: &Job) -> Result<Job> {
-        if self.providers_pool.is_empty() {
+        let mut idle_providers = self.idle_providers_for(job);
+        if idle_providers.is_empty() {
             error!(
                 "unexpected condition: no available provider. can't process job {}",
                 job.id
@@ -114,199 +338,830 @@ impl PosServer {
             bail!("no available provider for job execution");
         }
 
+        // the job is computed with its own params which may differ from the current server config
+        let task_config = self.job_config(job);
         if let Err(e) = job.validate(
-            self.config.indexes_per_compute_cycle,
-            self.config.bits_per_index,
+            task_config.indexes_per_compute_cycle,
+            task_config.bits_per_index,
         ) {
             error!("Invalid submitted job {}, {}", job, e);
             return Err(e);
         }
 
-        let provider_id = self.providers_pool.pop().unwrap();
+        let bits_per_index = task_config.bits_per_index as u64;
+        let indexes_per_cycle = task_config.indexes_per_compute_cycle;
         let mut task_job = job.clone();
-        task_job.pow_solution_index = u64::MAX;
         task_job.started = datetime::Instant::now().seconds() as u64;
         task_job.status = JobStatus::Started as i32;
-        task_job.compute_provider_id = provider_id;
+
+        if task_job.stripes.is_empty() && !task_job.is_pow_only() {
+            // a continued job starts at the first index which was not written
+            let first_idx = task_job.bits_written / bits_per_index;
+            if first_idx > 0 {
+                task_job
+                    .stripes
+                    .push(PosServer::new_stripe(0, first_idx - 1, first_idx));
+            }
+
+            let indexes = task_job.size_bits / bits_per_index - first_idx;
+            task_job.stripes.extend(PosServer::split_stripes(
+                first_idx,
+                indexes,
+                indexes_per_cycle,
+                idle_providers.len() as u64,
+            ));
+        }
+
+        // a provider for each stripe to compute or one for a pow search when all stripes are computed
+        let stripes_to_compute = task_job
+            .stripes
+            .iter()
+            .filter(|s| !s.is_completed())
+            .count();
+        let providers_count = idle_providers.len().min(stripes_to_compute.max(1));
+        let providers: Vec<u32> = (0..providers_count)
+            .map(|_| idle_providers.pop().unwrap())
+            .collect();
+        self.providers_pool.retain(|id| !providers.contains(id));
+
+        for (i, stripe) in task_job
+            .stripes
+            .iter_mut()
+            .filter(|s| !s.is_completed())
+            .enumerate()
+        {
+            stripe.compute_provider_id = providers[i % providers.len()];
+            stripe.last_error = None;
+        }
+        task_job.compute_provider_id = providers[0];
+        task_job.bits_written = task_job.indexes_written() * bits_per_index;
 
         self.jobs.insert(job.id, task_job.clone());
+        self.store_job(&task_job);
         // Job with updated data to return to caller (pre task completion)
         let res_job = task_job.clone();
-        let task_config = self.config.clone();
 
-        info!("starting task for job {}...", task_job.id);
+        // signal used by the server to abort the task
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.cancel_signals.insert(job.id, cancel.clone());
+        self.job_providers.insert(job.id, providers.clone());
+
+        info!(
+            "starting task for job {} on providers {:?}...",
+            task_job.id, providers
+        );
 
         // spawn a blocking task since the compute lib is blocking
-        let _handle = task::spawn_blocking(move || {
-            let bits_per_cycle =
-                task_config.indexes_per_compute_cycle * task_config.bits_per_index as u64;
-            let iterations = task_job.size_bits / bits_per_cycle;
-            let buff_size = (task_config.indexes_per_compute_cycle
-                * task_config.bits_per_index as u64) as usize;
-            let mut buffer = vec![0_u8; buff_size];
-            let mut hashes_computed: u64 = 0;
-            let mut hashes_per_sec: u64 = 0;
-            let mut idx_solution: u64 = u64::MAX;
-
-            // Pos will be saved in a file called <job_id>.pos in the dest data directory
-            let path = Path::new(task_config.data_dir.as_str())
-                .join(Path::new(format!("{}.pos", task_job.id).as_str()));
-
-            let file = match File::create(&path) {
-                Ok(file) => file,
-                Err(e) => {
-                    PosServer::task_error(
-                        &mut task_job,
-                        501,
-                        format!("error {} creating pos data file at {}.", path.display(), e),
-                    );
-                    return;
-                }
+        let backend = self.backend.clone();
+        let handle = task::spawn_blocking(move || {
+            let task_job = if task_job.is_pow_only() {
+                PosServer::run_pow_task(task_job, &task_config, backend.as_ref(), &cancel)
+            } else {
+                let task_job =
+                    PosServer::run_task(task_job, task_config.clone(), backend, cancel, providers);
+                // the final metadata reflects how the task stopped
+                PosServer::save_metadata(&task_job, &task_config);
+                task_job
             };
+            let _ = PosServer::update_job_status(&task_job);
+        });
+        self.tasks.insert(job.id, handle);
 
-            let mut file_writer = BufWriter::new(file);
-            let mut start_idx = 0;
+        Ok(res_job)
+    }
 
-            for i in 0..iterations {
-                start_idx = i * task_config.indexes_per_compute_cycle;
-                let end_idx = start_idx + task_config.indexes_per_compute_cycle - 1;
+    /// Search for pow solutions in a pow job's indexes range. No pos data is written.
+    /// Blocks until the job's solutions are found, the search fails or is canceled.
+    /// Returns the job with its final status which is not reported to the server.
+    fn run_pow_task(
+        mut job: Job,
+        config: &Config,
+        backend: &dyn ComputeBackend,
+        cancel: &AtomicBool,
+    ) -> Job {
+        let start_idx = job.pow_search_index;
+        let end_idx = job.size_bits / config.bits_per_index as u64 - 1;
+        let cycle_bytes = config.indexes_per_compute_cycle * config.bits_per_index as u64 / 8;
+        let mut buffer = vec![0_u8; cycle_bytes as usize];
 
-                info!(
-                    "job: {}. executing pos iter {} / {}, provider: {}. start_idx: {}, end_idx: {}",
-                    task_job.id,
-                    i + 1,
-                    iterations,
-                    task_job.compute_provider_id,
-                    start_idx,
-                    end_idx
+        match PosServer::find_pow_solutions(
+            &mut job,
+            config,
+            backend,
+            start_idx,
+            end_idx,
+            &mut buffer,
+            cancel,
+        ) {
+            Ok(true) => info!(
+                "found {} pow solutions. {} hashes searched",
+                job.pow_solutions.len(),
+                job.pow_hashes_searched
+            ),
+            Ok(false) => {
+                PosServer::task_canceled(&mut job);
+                return job;
+            }
+            Err(e) => {
+                let msg = format!("error computing pow solution: {}", e.message);
+                PosServer::task_error(&mut job, e.error, msg);
+                return job;
+            }
+        }
+
+        info!("job completed {}", job.id);
+        job.status = JobStatus::Completed as i32;
+        job.stopped = datetime::Instant::now().seconds() as u64;
+        job
+    }
+
+    /// Compute a job's pos data. Blocks until the job completes, fails or is canceled.
+    /// Returns the job with its final status which is not reported to the server.
+    fn run_task(
+        mut job: Job,
+        config: Config,
+        backend: Arc<dyn ComputeBackend>,
+        cancel: Arc<AtomicBool>,
+        providers: Vec<u32>,
+    ) -> Job {
+        let files = match DataFiles::new(&job, &config) {
+            Ok(files) => files,
+            Err(e) => {
+                let error = Error::from_error(&e, Error::Validation);
+                PosServer::task_error(
+                    &mut job,
+                    error as i32,
+                    format!("invalid pos data files. {}.", e),
                 );
+                return job;
+            }
+        };
+
+        PosServer::save_metadata(&job, &config);
+        let checkpoint_path = Checkpoint::path(config.data_dir.as_str(), job.id);
+
+        let job_id = job.id;
+        let task = Arc::new(Task {
+            config,
+            backend,
+            files,
+            checkpoint_path,
+            cancel,
+            failed_providers: Mutex::new(HashSet::new()),
+            status_updated: Mutex::new(None),
+            checkpoint_saving: Mutex::new(()),
+            state: Mutex::new(TaskState {
+                durable: job.stripes.iter().map(|s| s.next_index).collect(),
+                job,
+            }),
+        });
 
-                let options = match task_job.compute_pow_solution {
-                    true => match task_job.pow_solution_index {
-                        u64::MAX => {
-                            ComputeOptions::ComputeLeaves as u32 | ComputeOptions::ComputePow as u32
+        let mut providers = providers;
+        loop {
+            // each provider computes its stripes in a blocking worker
+            let workers: Vec<_> = providers
+                .iter()
+                .map(|provider_id| {
+                    let provider_id = *provider_id;
+                    let task = task.clone();
+                    task::spawn_blocking(move || task.run_stripes(provider_id))
+                })
+                .collect();
+
+            for worker in workers {
+                if let Err(e) = Handle::current().block_on(worker) {
+                    error!("stripes worker failed: {}", e);
+                }
+            }
+
+            if task.cancel.load(Ordering::Relaxed) {
+                break;
+            }
+
+            // the stripes of providers which kept failing are continued by other providers
+            let failed: Vec<u32> = task.failed_providers.lock().unwrap().drain().collect();
+            let mut failed_over = false;
+            for provider_id in failed {
+                providers.retain(|id| *id != provider_id);
+                match PosServer::failover_provider(job_id, provider_id) {
+                    Ok(Some(failover_id)) => {
+                        failed_over |= task.failover(provider_id, failover_id);
+                        if !providers.contains(&failover_id) {
+                            providers.push(failover_id);
                         }
-                        _ => ComputeOptions::ComputeLeaves as u32,
-                    },
-                    false => ComputeOptions::ComputeLeaves as u32,
-                };
+                    }
+                    Ok(None) => info!(
+                        "job {}: no provider can continue the stripes of provider {}",
+                        job_id, provider_id
+                    ),
+                    Err(e) => error!("job {}: provider failover failed: {}", job_id, e),
+                }
+            }
 
-                let res = compute_pos(
-                    task_job.compute_provider_id,
-                    task_job.client_id.as_ref(),
-                    start_idx,
-                    end_idx,
-                    task_config.bits_per_index,
-                    task_config.salt.as_ref(),
-                    options,
-                    &mut buffer,
-                    task_config.n,
-                    task_config.r,
-                    task_config.p,
-                    task_job.pow_difficulty.as_ref(),
-                    &mut idx_solution as *mut u64,
-                    &mut hashes_computed as *mut u64,
-                    &mut hashes_per_sec as *mut u64,
-                );
+            if !failed_over {
+                break;
+            }
+        }
 
-                if task_job.compute_pow_solution
-                    && task_job.pow_solution_index == u64::MAX
-                    && idx_solution != u64::MAX
-                {
-                    info!(
-                        "👊 found pow solution at index while computing leaves at: {}",
-                        idx_solution
-                    );
-                    task_job.pow_solution_index = idx_solution;
+        let mut job = task.state.lock().unwrap().job.clone();
+
+        if task.cancel.load(Ordering::Relaxed) {
+            PosServer::task_canceled(&mut job);
+            return job;
+        }
+
+        // failed stripes can be continued from their checkpoints with the data of all other stripes
+        let failed: Vec<&JobError> = job
+            .stripes
+            .iter()
+            .filter_map(|s| s.last_error.as_ref())
+            .collect();
+        if let Some(err) = failed.first() {
+            let (error, message) = (err.error, err.message.clone());
+            let msg = format!(
+                "{} of {} stripes failed. {}",
+                failed.len(),
+                job.stripes.len(),
+                message
+            );
+            PosServer::task_error(&mut job, error, msg);
+            return job;
+        }
+
+        info!("leaves compute finished {}", job.id);
+
+        if job.compute_pow_solution
+            && !job.all_pow_solutions
+            && job.pow_solutions.len() < job.pow_solutions_wanted()
+        {
+            // some pow solutions were not found in the job's data - look for them after the job's last index.
+            // A job which wants all solutions only searches its data range so its search never gets here.
+            let start_idx = job
+                .stripes
+                .iter()
+                .map(|s| s.end_index + 1)
+                .max()
+                .unwrap_or(0);
+            let mut buffer = vec![0_u8; task.cycle_bytes()];
+            job.pow_search_index = start_idx;
+            match PosServer::find_pow_solutions(
+                &mut job,
+                &task.config,
+                task.backend.as_ref(),
+                start_idx,
+                u64::MAX,
+                &mut buffer,
+                &task.cancel,
+            ) {
+                Ok(true) => {}
+                Ok(false) => {
+                    PosServer::task_canceled(&mut job);
+                    return job;
                 }
+                Err(e) => {
+                    let msg = format!("error computing pow solution: {}", e.message);
+                    PosServer::task_error(&mut job, e.error, msg);
+                    return job;
+                }
+            }
+        }
+
+        info!("job completed {}", job.id);
+        // task was running and didn't stop due to an error so mark it as complete
+        job.status = JobStatus::Completed as i32;
+        job.stopped = datetime::Instant::now().seconds() as u64;
+
+        // a completed job can't be continued
+        if let Err(e) = Checkpoint::delete(&task.checkpoint_path) {
+            error!(
+                "failed to delete checkpoint {}: {}",
+                task.checkpoint_path.display(),
+                e
+            );
+        }
+        job
+    }
+}
+
+/// Number of buffers used by a stripe's compute loop and writer
+const WRITE_BUFFERS: usize = 2;
+
+/// Min time between a task's job progress updates
+const STATUS_UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A job's task which is shared by the blocking workers which compute the job's stripes
+struct Task {
+    config: Config,
+    backend: Arc<dyn ComputeBackend>,
+    files: DataFiles,
+    checkpoint_path: PathBuf,
+    cancel: Arc<AtomicBool>,
+    failed_providers: Mutex<HashSet<u32>>, // providers which failed a cycle after all of its retries
+    status_updated: Mutex<Option<time::Instant>>, // time of the last job status update. Held while an update is sent
+    checkpoint_saving: Mutex<()>, // held while the job's checkpoint is saved so checkpoints are saved in order
+    state: Mutex<TaskState>,
+}
+
+/// A job's task state which is updated by the task's workers
+struct TaskState {
+    job: Job,
+    durable: Vec<u64>, // next durably stored index of each of the job's stripes
+}
+
+impl Task {
+    /// Returns the size in bytes of the data of one compute cycle
+    fn cycle_bytes(&self) -> usize {
+        (self.config.indexes_per_compute_cycle * self.config.bits_per_index as u64 / 8) as usize
+    }
+
+    /// Send a snapshot of the task's job to the server. Progress updates are sent at most once per
+    /// STATUS_UPDATE_INTERVAL and skipped while another update is sent. Forced updates are always sent.
+    /// The state lock isn't held while the server processes the update so workers aren't blocked by it.
+    fn update_job_status(&self, force: bool) {
+        let mut status_updated = match force {
+            true => self.status_updated.lock().unwrap(),
+            false => match self.status_updated.try_lock() {
+                Ok(status_updated) => match *status_updated {
+                    Some(updated) if updated.elapsed() < STATUS_UPDATE_INTERVAL => return,
+                    _ => status_updated,
+                },
+                Err(_) => return,
+            },
+        };
+        let job = self.state.lock().unwrap().job.clone();
+        let _ = PosServer::update_job_status(&job);
+        *status_updated = Some(time::Instant::now());
+    }
+
+    /// Compute the stripes of the job which are assigned to a provider one after the other.
+    /// A failed stripe is stopped and its error is recorded without stopping other stripes.
+    /// A provider which failed a cycle after all of its retries stops computing its stripes.
+    fn run_stripes(self: Arc<Self>, provider_id: u32) {
+        let stripes: Vec<usize> = {
+            let state = self.state.lock().unwrap();
+            state
+                .job
+                .stripes
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| {
+                    s.compute_provider_id == provider_id
+                        && !s.is_completed()
+                        && s.last_error.is_none()
+                })
+                .map(|(i, _)| i)
+                .collect()
+        };
 
-                if res != ComputeResults::NoError as i32
-                    && res != ComputeResults::PowSolutionFound as i32
+        for stripe in stripes {
+            if self.cancel.load(Ordering::Relaxed)
+                || self.failed_providers.lock().unwrap().contains(&provider_id)
+            {
+                break;
+            }
+
+            if let Err(e) = self.clone().run_stripe(provider_id, stripe) {
                 {
-                    let result = ComputeResults::try_from(res).unwrap();
-                    info!("compute result: {}", result);
-                    PosServer::task_error(
-                        &mut task_job,
-                        501,
-                        format!("gpu compute error. Unexpected result: {}", result),
-                    );
-                    break;
+                    let mut state = self.state.lock().unwrap();
+                    error!("job {}: {}", state.job.id, e.message);
+                    state.job.stripes[stripe].last_error = Some(e);
                 }
+                self.update_job_status(true);
+            }
+        }
+    }
 
-                if hashes_computed < task_config.indexes_per_compute_cycle {
-                    PosServer::task_error(
-                        &mut task_job,
-                        502,
-                        format!(
-                            "gpu compute error. Hashes computed: {}. Expected:{}",
-                            hashes_computed, task_config.indexes_per_compute_cycle
-                        ),
-                    );
-                    break;
+    /// Move the incomplete stripes of a failed provider to another provider and record the failover in the job's history.
+    /// Returns true if any stripe was moved.
+    fn failover(&self, provider_id: u32, failover_id: u32) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let job = &mut state.job;
+        let mut retries = vec![];
+        for (i, stripe) in job.stripes.iter_mut().enumerate() {
+            if stripe.compute_provider_id != provider_id || stripe.is_completed() {
+                continue;
+            }
+            info!(
+                "job {}: stripe {} fails over from provider {} to provider {}",
+                job.id, i, provider_id, failover_id
+            );
+            stripe.compute_provider_id = failover_id;
+            retries.push(ComputeRetry {
+                action: Action::Failover as i32,
+                time: datetime::Instant::now().seconds() as u64,
+                stripe: i as u32,
+                provider_id,
+                start_index: stripe.next_index,
+                end_index: stripe.end_index,
+                attempt: 0,
+                failover_provider_id: failover_id,
+                error: stripe.last_error.take(),
+            });
+        }
+
+        if retries.is_empty() {
+            return false;
+        }
+        job.retries.extend(retries);
+        drop(state);
+
+        self.update_job_status(true);
+        true
+    }
+
+    /// Returns the error of a compute cycle's result or None if the cycle was computed
+    fn cycle_error(
+        res: &Result<ComputeOutcome, ComputeError>,
+        expected_hashes: u64,
+    ) -> Option<JobError> {
+        let hashes_computed = match res {
+            Ok(outcome) => outcome.hashes_computed,
+            Err(e) => {
+                let err = PosServer::compute_error(e, "gpu compute error. Unexpected result");
+                info!("compute result: {}", err.message);
+                return Some(err);
+            }
+        };
+
+        if hashes_computed < expected_hashes {
+            return Some(JobError {
+                error: Error::GpuComputeError as i32,
+                message: format!(
+                    "gpu compute error. Hashes computed: {}. Expected:{}",
+                    hashes_computed, expected_hashes
+                ),
+            });
+        }
+        None
+    }
+
+    /// Record a retry of a stripe's failed cycle in the job's history and wait for the retry's backoff delay
+    fn retry_cycle(
+        &self,
+        stripe: usize,
+        provider_id: u32,
+        range: (u64, u64),
+        attempt: u32,
+        error: JobError,
+    ) {
+        {
+            let mut state = self.state.lock().unwrap();
+            warn!(
+                "job {}: retrying cycle [{}, {}] on provider {}. attempt {}. {}",
+                state.job.id, range.0, range.1, provider_id, attempt, error.message
+            );
+            state.job.retries.push(ComputeRetry {
+                action: Action::Retry as i32,
+                time: datetime::Instant::now().seconds() as u64,
+                stripe: stripe as u32,
+                provider_id,
+                start_index: range.0,
+                end_index: range.1,
+                attempt,
+                failover_provider_id: u32::MAX,
+                error: Some(error),
+            });
+        }
+
+        let backoff = (self.config.retry_backoff_ms as u64) << (attempt - 1).min(16);
+        thread::sleep(Duration::from_millis(backoff));
+    }
+
+    /// Returns an error of a stripe
+    fn stripe_error(stripe: usize, error: i32, message: String) -> JobError {
+        JobError {
+            error,
+            message: format!("stripe {}: {}", stripe, message),
+        }
+    }
+
+    /// Compute a stripe of the job from its next index on a provider.
+    /// Computed cycles are written by a dedicated writer so the provider computes the next cycle
+    /// while the previous one is written. The provider only waits for the writer when all
+    /// WRITE_BUFFERS buffers are waiting to be written.
+    fn run_stripe(self: Arc<Self>, provider_id: u32, stripe: usize) -> Result<(), JobError> {
+        let (job, stripe_data) = {
+            let state = self.state.lock().unwrap();
+            (state.job.clone(), state.job.stripes[stripe].clone())
+        };
+        let config = &self.config;
+
+        let data_writer = self
+            .files
+            .clone()
+            .open_writer(stripe_data.next_index)
+            .map_err(|e| {
+                Task::stripe_error(
+                    stripe,
+                    Error::from_error(&e, Error::IoError) as i32,
+                    format!("error opening pos data files. {}.", e),
+                )
+            })?;
+
+        // computed buffers are sent to the writer which returns them once they were written
+        let (free_sender, free_receiver) = mpsc::channel();
+        for _ in 0..WRITE_BUFFERS {
+            free_sender.send(vec![0_u8; self.cycle_bytes()]).unwrap();
+        }
+        let (full_sender, full_receiver) = mpsc::sync_channel(WRITE_BUFFERS);
+
+        let task = self.clone();
+        let next_index = stripe_data.next_index;
+        let writer = task::spawn_blocking(move || {
+            task.write_stripe(stripe, next_index, data_writer, full_receiver, free_sender)
+        });
+
+        let indexes_per_cycle = config.indexes_per_compute_cycle;
+        let indexes = stripe_data.end_index + 1 - stripe_data.next_index;
+        let iterations = (indexes + indexes_per_cycle - 1) / indexes_per_cycle;
+        let mut start_idx = stripe_data.next_index;
+        let mut pow_buffer: Vec<u8> = vec![]; // allocated when a cycle has more than one pow solution
+        let mut result = Ok(());
+
+        for i in 0..iterations {
+            if self.cancel.load(Ordering::Relaxed) {
+                break;
+            }
+
+            // wait for a free buffer. The writer drops its end when it stops due to an error
+            let mut buffer: Vec<u8> = match free_receiver.recv() {
+                Ok(buffer) => buffer,
+                Err(_) => break,
+            };
+
+            // the stripe's last cycle is partial when the stripe is not a multiple of cycles
+            let end_idx = (start_idx + indexes_per_cycle - 1).min(stripe_data.end_index);
+
+            info!(
+                "job: {}. stripe {}. executing pos iter {} / {}, provider: {}. start_idx: {}, end_idx: {}",
+                job.id,
+                stripe,
+                i + 1,
+                iterations,
+                provider_id,
+                start_idx,
+                end_idx
+            );
+
+            let compute_pow = self
+                .state
+                .lock()
+                .unwrap()
+                .job
+                .needs_pow_solution_at(start_idx);
+            let options = match compute_pow {
+                true => ComputeOptions::ComputeLeaves as u32 | ComputeOptions::ComputePow as u32,
+                false => ComputeOptions::ComputeLeaves as u32,
+            };
+
+            // a failed cycle is retried on the provider before the provider is failed
+            let request = PosServer::compute_request(
+                &job,
+                config,
+                provider_id,
+                (start_idx, end_idx),
+                options,
+            );
+            let mut attempt: u32 = 0;
+            let res = loop {
+                let res = self.backend.compute(&request, &mut buffer);
+
+                if res == Err(ComputeError::Canceled) && self.cancel.load(Ordering::Relaxed) {
+                    break res;
                 }
 
-                match file_writer.write_all(&buffer) {
-                    Ok(..) => info!(
-                        "job {} wrote {} bytes to {}",
-                        task_job.id,
-                        buff_size,
-                        path.display()
-                    ),
-                    Err(e) => {
-                        PosServer::task_error(
-                            &mut task_job,
-                            501,
-                            format!("error writing to pos data file: {} {}", path.display(), e),
-                        );
-                        break;
-                    }
+                let err = match Task::cycle_error(&res, end_idx + 1 - start_idx) {
+                    Some(err) => Task::stripe_error(stripe, err.error, err.message),
+                    None => break res,
+                };
+                if self.cancel.load(Ordering::Relaxed) {
+                    result = Err(err);
+                    break res;
                 }
+                // retrying a cycle on a provider which the compute lib doesn't have is pointless
+                if attempt >= config.compute_retries || err.error == Error::InvalidProvider as i32 {
+                    self.failed_providers.lock().unwrap().insert(provider_id);
+                    result = Err(err);
+                    break res;
+                }
+                attempt += 1;
+                self.retry_cycle(stripe, provider_id, (start_idx, end_idx), attempt, err);
+            };
 
-                task_job.bits_written += bits_per_cycle;
-                let _ = PosServer::update_job_status(&task_job);
+            if res == Err(ComputeError::Canceled) && self.cancel.load(Ordering::Relaxed) {
+                // current cycle was interrupted by the server - its output is incomplete
+                break;
             }
 
-            info!("leaves compute finished {}", task_job.id);
+            if result.is_err() {
+                break;
+            }
 
-            if let Err(e) = file_writer.flush() {
-                PosServer::task_error(
-                    &mut task_job,
-                    501,
-                    format!("error flushing pos file {}. {}.", path.display(), e),
+            let idx_solution = match res {
+                Ok(ComputeOutcome {
+                    solution: Some(idx_solution),
+                    ..
+                }) => idx_solution,
+                _ => u64::MAX,
+            };
+            if compute_pow && idx_solution != u64::MAX {
+                info!(
+                    "👊 found pow solution at index while computing leaves at: {}",
+                    idx_solution
                 );
-                return;
+                self.state
+                    .lock()
+                    .unwrap()
+                    .job
+                    .add_pow_solutions(&[idx_solution]);
             }
 
-            if task_job.compute_pow_solution && task_job.pow_solution_index == u64::MAX {
-                // pow solution not found yet - look for it starting at start_index using existing buffer so
-                // no additional memory allocation is needed
-                match PosServer::find_pow_solution(&task_job, &task_config, start_idx, &mut buffer)
-                {
-                    Ok(solution) => {
-                        info!("👊 Pow solution found at index: {}", solution);
-                        task_job.pow_solution_index = solution;
+            // the compute lib reports the first solution of a cycle - search the rest of the cycle for the missing solutions.
+            // Solutions up to the cycle's solution are found. Solutions of other stripes after it may be replaced.
+            let missing = match compute_pow && idx_solution < end_idx {
+                true => {
+                    let state = self.state.lock().unwrap();
+                    match state.job.needs_pow_solution_at(idx_solution + 1) {
+                        true => {
+                            let found = state
+                                .job
+                                .pow_solutions
+                                .iter()
+                                .filter(|s| **s <= idx_solution)
+                                .count();
+                            state.job.pow_solutions_wanted().saturating_sub(found)
+                        }
+                        false => 0,
                     }
+                }
+                false => 0,
+            };
+            if missing > 0 {
+                if pow_buffer.is_empty() {
+                    pow_buffer = vec![0_u8; self.cycle_bytes()];
+                }
+                let request = ComputeRequest {
+                    start_position: idx_solution + 1,
+                    options: ComputeOptions::ComputePow as u32,
+                    ..request.clone()
+                };
+                match PosServer::search_pow_range(
+                    self.backend.as_ref(),
+                    request,
+                    missing,
+                    &mut pow_buffer,
+                    &self.cancel,
+                ) {
+                    Ok(Some((solutions, hashes))) => {
+                        let mut state = self.state.lock().unwrap();
+                        state.job.add_pow_solutions(&solutions);
+                        state.job.pow_hashes_searched += hashes;
+                    }
+                    Ok(None) => break,
                     Err(e) => {
-                        PosServer::task_error(
-                            &mut task_job,
-                            501,
-                            format!("error computing pow solution {}", e),
-                        );
-                        return;
+                        result = Err(Task::stripe_error(stripe, e.error, e.message));
+                        break;
                     }
                 }
             }
 
-            if task_job.status == JobStatus::Started as i32 {
-                info!("job completed {}", task_job.id);
-                // if task was running and didn't stop due to an error then mark it as complete
-                task_job.status = JobStatus::Completed as i32;
-                task_job.stopped = datetime::Instant::now().seconds() as u64;
+            start_idx = end_idx + 1;
+            if full_sender.send((buffer, start_idx)).is_err() {
+                // writer stopped due to an error
+                break;
             }
+        }
 
-            let _ = PosServer::update_job_status(&task_job);
-        });
+        // let the writer write all computed cycles and stop
+        drop(full_sender);
+        let write_result = match Handle::current().block_on(writer) {
+            Ok(res) => res,
+            Err(e) => Err(Task::stripe_error(
+                stripe,
+                Error::Unknown as i32,
+                format!("pos data writer failed. {}", e),
+            )),
+        };
 
-        Ok(res_job)
+        match result {
+            Ok(()) => write_result,
+            Err(e) => {
+                if let Err(write_err) = write_result {
+                    error!("job {}: {}", job.id, write_err.message);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Write a stripe's computed cycles from next_index and update the stripe's progress.
+    /// Written buffers are returned to the stripe's compute loop.
+    fn write_stripe(
+        &self,
+        stripe: usize,
+        mut next_index: u64,
+        mut data_writer: DataWriter,
+        full_receiver: Receiver<(Vec<u8>, u64)>,
+        free_sender: Sender<Vec<u8>>,
+    ) -> Result<(), JobError> {
+        let checkpoint_interval = self.config.checkpoint_interval.max(1) as u64;
+        let mut cycles_since_checkpoint: u64 = 0;
+        let mut result = Ok(());
+
+        for (buffer, buffer_next_index) in full_receiver.iter() {
+            // a partial cycle only fills the start of the buffer
+            let len =
+                ((buffer_next_index - next_index) * self.config.bits_per_index as u64 / 8) as usize;
+            if let Err(e) = data_writer.write_all(&buffer[..len]) {
+                result = Err(Task::stripe_error(
+                    stripe,
+                    Error::from(&e) as i32,
+                    format!(
+                        "error writing to pos data file: {} {}",
+                        data_writer.path().display(),
+                        e
+                    ),
+                ));
+                break;
+            }
+
+            next_index = buffer_next_index;
+            cycles_since_checkpoint += 1;
+            {
+                let mut state = self.state.lock().unwrap();
+                info!(
+                    "job {} wrote {} bytes to {}",
+                    state.job.id,
+                    len,
+                    data_writer.path().display()
+                );
+                state.job.stripes[stripe].next_index = next_index;
+                state.job.bits_written =
+                    state.job.indexes_written() * self.config.bits_per_index as u64;
+            }
+
+            if cycles_since_checkpoint == checkpoint_interval {
+                cycles_since_checkpoint = 0;
+                if let Err(e) = self.sync_checkpoint(&mut data_writer, stripe, next_index) {
+                    result = Err(Task::stripe_error(
+                        stripe,
+                        Error::from_error(&e, Error::IoError) as i32,
+                        format!(
+                            "error syncing pos file {}. {}.",
+                            data_writer.path().display(),
+                            e
+                        ),
+                    ));
+                    break;
+                }
+            }
+
+            self.update_job_status(false);
+
+            // compute loop stopped - no need to return the buffer
+            let _ = free_sender.send(buffer);
+        }
+
+        // sync all written data so a stopped stripe can be continued from its checkpoint
+        if let Err(e) = self.sync_checkpoint(&mut data_writer, stripe, next_index) {
+            let err = Task::stripe_error(
+                stripe,
+                Error::from_error(&e, Error::IoError) as i32,
+                format!(
+                    "error syncing pos file {}. {}.",
+                    data_writer.path().display(),
+                    e
+                ),
+            );
+            match result {
+                Ok(()) => result = Err(err),
+                Err(_) => error!("{}", err.message),
+            }
+        }
+
+        // report the stripe's final progress
+        self.update_job_status(true);
+        result
+    }
+
+    /// Sync a stripe's written pos data to disk and record its last durable index in the job's checkpoint
+    fn sync_checkpoint(
+        &self,
+        data_writer: &mut DataWriter,
+        stripe: usize,
+        next_index: u64,
+    ) -> Result<()> {
+        data_writer.sync()?;
+
+        // the state lock isn't held while the checkpoint is written so workers aren't blocked by disk io
+        let _saving = self.checkpoint_saving.lock().unwrap();
+        let (job, durable) = {
+            let mut state = self.state.lock().unwrap();
+            state.durable[stripe] = next_index;
+            (state.job.clone(), state.durable.clone())
+        };
+        Checkpoint::new(&job, &self.config, &durable).save(&self.checkpoint_path)?;
+        PosServer::save_metadata(&job, &self.config);
+        Ok(())
     }
 }
@@ -0,0 +1,170 @@
+use anyhow::{bail, Result};
+use pos_api::api::Job;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Known compute provider classes which may have a scheduling policy
+const PROVIDER_CLASSES: [&str; 3] = ["cpu", "cuda", "vulkan"];
+
+/// Scheduling policy of a compute providers class.
+/// A job is only scheduled on providers of classes which admit it.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct ProviderClassPolicy {
+    /// smallest job size (bits) computed by the class providers
+    pub(crate) min_job_size_bits: u64,
+    /// largest job size (bits) computed by the class providers. No limit when 0
+    pub(crate) max_job_size_bits: u64,
+    /// lowest job priority computed by the class providers
+    pub(crate) min_priority: u32,
+    /// highest job priority computed by the class providers. No limit when unset
+    pub(crate) max_priority: Option<u32>,
+}
+
+impl ProviderClassPolicy {
+    /// Returns true iff providers of the policy's class may compute the job
+    pub(crate) fn admits(&self, job: &Job) -> bool {
+        job.size_bits >= self.min_job_size_bits
+            && (self.max_job_size_bits == 0 || job.size_bits <= self.max_job_size_bits)
+            && job.priority >= self.min_priority
+            && !matches!(self.max_priority, Some(max) if job.priority > max)
+    }
+}
+
+/// Providers classes policies keyed by lower-case class name (cpu, cuda or vulkan).
+/// Classes without a policy compute any job.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProviderPolicies(HashMap<String, ProviderClassPolicy>);
+
+impl ProviderPolicies {
+    /// Create policies from config classes policies. Unknown classes are rejected
+    pub(crate) fn new(policies: HashMap<String, ProviderClassPolicy>) -> Result<Self> {
+        let mut res = HashMap::new();
+        for (class, policy) in policies {
+            let class = class.to_lowercase();
+            if !PROVIDER_CLASSES.contains(&class.as_str()) {
+                bail!("unknown compute provider class in policy: {}", class)
+            }
+            if policy.max_job_size_bits != 0 && policy.max_job_size_bits < policy.min_job_size_bits
+            {
+                bail!("{} policy admits no job size", class)
+            }
+            if matches!(policy.max_priority, Some(max) if max < policy.min_priority) {
+                bail!("{} policy admits no job priority", class)
+            }
+            res.insert(class, policy);
+        }
+        Ok(ProviderPolicies(res))
+    }
+
+    /// Returns true iff providers of a class (as returned by get_provider_class_string) may compute the job
+    pub(crate) fn admits(&self, class: &str, job: &Job) -> bool {
+        match self.0.get(&class.to_lowercase()) {
+            Some(policy) => policy.admits(job),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(size_bits: u64, priority: u32) -> Job {
+        Job {
+            size_bits,
+            priority,
+            ..Job::default()
+        }
+    }
+
+    fn class_policies(class: &str, policy: ProviderClassPolicy) -> Result<ProviderPolicies> {
+        ProviderPolicies::new(vec![(class.to_string(), policy)].into_iter().collect())
+    }
+
+    #[test]
+    fn job_size_limits() {
+        let policies = class_policies(
+            "CUDA",
+            ProviderClassPolicy {
+                min_job_size_bits: 1024,
+                max_job_size_bits: 4096,
+                ..ProviderClassPolicy::default()
+            },
+        )
+        .unwrap();
+        assert!(!policies.admits("cuda", &job(512, 0)));
+        assert!(policies.admits("cuda", &job(1024, 0)));
+        assert!(policies.admits("Cuda", &job(4096, 0)));
+        assert!(!policies.admits("cuda", &job(8192, 0)));
+
+        // classes without a policy compute any job
+        assert!(policies.admits("cpu", &job(512, 0)));
+        assert!(policies.admits("vulkan", &job(8192, 0)));
+    }
+
+    #[test]
+    fn no_max_job_size() {
+        let policies = class_policies(
+            "cpu",
+            ProviderClassPolicy {
+                min_job_size_bits: 1024,
+                ..ProviderClassPolicy::default()
+            },
+        )
+        .unwrap();
+        assert!(policies.admits("cpu", &job(u64::MAX, 0)));
+    }
+
+    #[test]
+    fn priority_limits() {
+        let policies = class_policies(
+            "vulkan",
+            ProviderClassPolicy {
+                min_priority: 2,
+                max_priority: Some(5),
+                ..ProviderClassPolicy::default()
+            },
+        )
+        .unwrap();
+        assert!(!policies.admits("vulkan", &job(1024, 1)));
+        assert!(policies.admits("vulkan", &job(1024, 2)));
+        assert!(policies.admits("vulkan", &job(1024, 5)));
+        assert!(!policies.admits("vulkan", &job(1024, 6)));
+
+        // a max priority of 0 only admits priority 0 jobs
+        let policies = class_policies(
+            "vulkan",
+            ProviderClassPolicy {
+                max_priority: Some(0),
+                ..ProviderClassPolicy::default()
+            },
+        )
+        .unwrap();
+        assert!(policies.admits("vulkan", &job(1024, 0)));
+        assert!(!policies.admits("vulkan", &job(1024, 1)));
+    }
+
+    #[test]
+    fn invalid_policies() {
+        assert!(class_policies("opencl", ProviderClassPolicy::default()).is_err());
+        assert!(class_policies(
+            "cpu",
+            ProviderClassPolicy {
+                min_job_size_bits: 4096,
+                max_job_size_bits: 1024,
+                ..ProviderClassPolicy::default()
+            },
+        )
+        .is_err());
+        assert!(class_policies(
+            "cpu",
+            ProviderClassPolicy {
+                min_priority: 3,
+                max_priority: Some(2),
+                ..ProviderClassPolicy::default()
+            },
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,230 @@
+use anyhow::{bail, Result};
+use pos_api::api::Job;
+
+/// A jobs scheduler decides where queued jobs are placed in the jobs queue.
+/// Queued jobs are started in queue order when providers which may compute them are idle.
+pub(crate) trait Scheduler: Send + Sync {
+    /// Returns the index in the queue at which a new job should be queued
+    fn queue_index(&self, queue: &[Job], job: &Job) -> usize;
+}
+
+/// Starts jobs in the order they were queued. Jobs priorities are ignored
+pub(crate) struct FifoScheduler;
+
+impl Scheduler for FifoScheduler {
+    fn queue_index(&self, queue: &[Job], _job: &Job) -> usize {
+        queue.len()
+    }
+}
+
+/// Starts jobs with a higher priority first and jobs with the same priority in the order they were queued
+pub(crate) struct PriorityScheduler;
+
+impl Scheduler for PriorityScheduler {
+    fn queue_index(&self, queue: &[Job], job: &Job) -> usize {
+        queue
+            .iter()
+            .rposition(|j| j.priority >= job.priority)
+            .map_or(0, |idx| idx + 1)
+    }
+}
+
+/// Returns a scheduler by its config name: fifo or priority
+pub(crate) fn new_scheduler(name: &str) -> Result<Box<dyn Scheduler>> {
+    match name.trim().to_lowercase().as_str() {
+        "fifo" => Ok(Box::new(FifoScheduler)),
+        "priority" => Ok(Box::new(PriorityScheduler)),
+        _ => bail!(
+            "unknown jobs scheduler: {}. expected fifo or priority",
+            name
+        ),
+    }
+}
+
+/// Queued jobs in the order they are started.
+/// The queue sets the 1-based queue position of its jobs.
+pub(crate) struct JobsQueue {
+    jobs: Vec<Job>,
+    scheduler: Box<dyn Scheduler>,
+}
+
+impl Default for JobsQueue {
+    fn default() -> Self {
+        JobsQueue::new(Box::new(FifoScheduler))
+    }
+}
+
+impl JobsQueue {
+    pub(crate) fn new(scheduler: Box<dyn Scheduler>) -> Self {
+        JobsQueue {
+            jobs: vec![],
+            scheduler,
+        }
+    }
+
+    /// Set the scheduler used to queue new jobs. Queued jobs keep their positions
+    pub(crate) fn set_scheduler(&mut self, scheduler: Box<dyn Scheduler>) {
+        self.scheduler = scheduler;
+    }
+
+    /// Queue a job at the index chosen by the scheduler
+    pub(crate) fn push(&mut self, job: Job) {
+        let idx = self.scheduler.queue_index(&self.jobs, &job);
+        self.jobs.insert(idx, job);
+        self.update_positions();
+    }
+
+    /// Queue a job at the end of the queue. Used to restore a stored queue in its positions order
+    pub(crate) fn push_back(&mut self, job: Job) {
+        self.jobs.push(job);
+        self.update_positions();
+    }
+
+    /// Remove and return a queued job
+    pub(crate) fn remove(&mut self, id: u64) -> Option<Job> {
+        let idx = self.jobs.iter().position(|j| j.id == id)?;
+        Some(self.remove_at(idx))
+    }
+
+    fn remove_at(&mut self, idx: usize) -> Job {
+        let mut job = self.jobs.remove(idx);
+        job.queue_position = 0;
+        self.update_positions();
+        job
+    }
+
+    /// Replace a queued job with an updated job. Returns false if the job isn't queued
+    pub(crate) fn replace(&mut self, job: &Job) -> bool {
+        match self.jobs.iter_mut().find(|j| j.id == job.id) {
+            Some(queued) => {
+                *queued = Job {
+                    queue_position: queued.queue_position,
+                    ..job.clone()
+                };
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move a queued job to a 1-based queue position.
+    /// A job moved to a position after the last job is moved to the end of the queue
+    pub(crate) fn move_job(&mut self, id: u64, position: u32) -> Result<()> {
+        if position == 0 {
+            bail!("queue positions start at 1")
+        }
+        let job = match self.jobs.iter().position(|j| j.id == id) {
+            Some(idx) => self.jobs.remove(idx),
+            None => bail!("job {} is not queued", id),
+        };
+        let idx = (position as usize - 1).min(self.jobs.len());
+        self.jobs.insert(idx, job);
+        self.update_positions();
+        Ok(())
+    }
+
+    pub(crate) fn get(&self, id: u64) -> Option<&Job> {
+        self.jobs.iter().find(|j| j.id == id)
+    }
+
+    pub(crate) fn contains(&self, id: u64) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Returns the queued jobs in queue order
+    pub(crate) fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    fn update_positions(&mut self) {
+        for (i, job) in self.jobs.iter_mut().enumerate() {
+            job.queue_position = i as u32 + 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: u64, priority: u32) -> Job {
+        Job {
+            id,
+            priority,
+            ..Job::default()
+        }
+    }
+
+    fn queued_ids(queue: &JobsQueue) -> Vec<u64> {
+        queue.jobs().iter().map(|j| j.id).collect()
+    }
+
+    #[test]
+    fn fifo_scheduler_ignores_priorities() {
+        let mut queue = JobsQueue::new(new_scheduler("fifo").unwrap());
+        queue.push(job(1, 0));
+        queue.push(job(2, 5));
+        queue.push(job(3, 1));
+        assert_eq!(queued_ids(&queue), vec![1, 2, 3]);
+        let positions: Vec<u32> = queue.jobs().iter().map(|j| j.queue_position).collect();
+        assert_eq!(positions, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn priority_scheduler_queues_higher_priorities_first() {
+        let mut queue = JobsQueue::new(new_scheduler(" Priority ").unwrap());
+        queue.push(job(1, 0));
+        queue.push(job(2, 5));
+        queue.push(job(3, 1));
+        queue.push(job(4, 5));
+        queue.push(job(5, 0));
+        // jobs with the same priority keep their queue order
+        assert_eq!(queued_ids(&queue), vec![2, 4, 3, 1, 5]);
+    }
+
+    #[test]
+    fn unknown_scheduler() {
+        assert!(new_scheduler("lifo").is_err());
+    }
+
+    #[test]
+    fn move_job() {
+        let mut queue = JobsQueue::default();
+        for id in 1..=4 {
+            queue.push(job(id, 0));
+        }
+
+        queue.move_job(4, 1).unwrap();
+        assert_eq!(queued_ids(&queue), vec![4, 1, 2, 3]);
+        assert_eq!(queue.get(4).unwrap().queue_position, 1);
+
+        // a position after the last job moves the job to the end of the queue
+        queue.move_job(1, 10).unwrap();
+        assert_eq!(queued_ids(&queue), vec![4, 2, 3, 1]);
+        assert_eq!(queue.get(1).unwrap().queue_position, 4);
+
+        assert!(queue.move_job(2, 0).is_err());
+        assert!(queue.move_job(7, 1).is_err());
+        assert_eq!(queued_ids(&queue), vec![4, 2, 3, 1]);
+    }
+
+    #[test]
+    fn removed_job_has_no_position() {
+        let mut queue = JobsQueue::default();
+        for id in 1..=3 {
+            queue.push(job(id, 0));
+        }
+        let removed = queue.remove(2).unwrap();
+        assert_eq!(removed.queue_position, 0);
+        assert_eq!(queue.get(3).unwrap().queue_position, 2);
+        assert!(queue.remove(2).is_none());
+    }
+}
@@ -1,15 +1,34 @@
 use crate::api::pos_grpc_service::PosGrpcService;
-use crate::{DEFAULT_BITS_PER_INDEX, DEFAULT_INDEXES_PER_CYCLE, DEFAULT_SALT};
+use crate::checkpoint::Checkpoint;
+use crate::data_files::DataFiles;
+use crate::job_store::JobStore;
+use crate::metadata::JobMetadata;
+use crate::provider_policy::ProviderPolicies;
+use crate::scheduler::{JobsQueue, Scheduler};
+use crate::{
+    DEFAULT_BITS_PER_INDEX, DEFAULT_CHECKPOINT_INTERVAL, DEFAULT_COMPUTE_RETRIES,
+    DEFAULT_INDEXES_PER_CYCLE, DEFAULT_POW_SEARCH_FACTOR, DEFAULT_QUARANTINE_FAILURES,
+    DEFAULT_RETRY_BACKOFF_MS, DEFAULT_SALT,
+};
 use anyhow::{bail, Result};
 use pos_api::api::job::JobStatus;
+use pos_api::api::job_error::Error;
 use pos_api::api::pos_data_service_server::PosDataServiceServer;
 use pos_api::api::{
-    AbortJobRequest, AddJobRequest, Config, Job, JobStatusStreamResponse, Provider,
+    AbortJobRequest, AddJobRequest, ComputeParams, Config, Job, JobError, JobStatusStreamResponse,
+    JobStripe, Provider, ProviderFilter,
 };
-use pos_compute::{get_providers, PosComputeProvider, COMPUTE_API_CLASS_CPU};
-use std::collections::HashMap;
+use pos_compute::{ComputeBackend, PosComputeProvider, ScryptBackend, COMPUTE_API_CLASS_CPU};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::Sender;
+use tokio::task;
+use tokio::task::JoinHandle;
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::transport::Server;
 use tonic::Status;
@@ -19,16 +38,30 @@ use xactor::*;
 /// The service manages a pool of compute providers (gpus) and schedules
 /// client-submitted jobs to use these providers to create pos data and to report job
 /// progress and errors to clients.
-/// todo: support aborting an in-progress job
 pub(crate) struct PosServer {
-    providers: Vec<PosComputeProvider>,  // gpu compute providers
-    pending_jobs: Vec<Job>,              // pending
-    pub(crate) jobs: HashMap<u64, Job>,  // in progress
-    pub(crate) config: Config,           // compute config
-    pub(crate) providers_pool: Vec<u32>, // idle providers
+    pub(crate) backend: Arc<dyn ComputeBackend>, // computes jobs with the providers
+    providers: Vec<PosComputeProvider>,          // gpu compute providers
+    pending_jobs: JobsQueue,                     // queued jobs in start order
+    pub(crate) jobs: HashMap<u64, Job>,          // in progress
+    pub(crate) config: Config,                   // compute config
+    pub(crate) providers_pool: Vec<u32>,         // idle providers
+    pub(crate) cancel_signals: HashMap<u64, Arc<AtomicBool>>, // abort signals of in-progress jobs tasks
+    pub(crate) job_providers: HashMap<u64, Vec<u32>>, // providers used by in-progress jobs tasks
+    pub(crate) tasks: HashMap<u64, JoinHandle<()>>,   // in-progress jobs tasks
+    disabled_providers: HashSet<u32>,                 // providers which are not used for jobs
+    hash_rates: HashMap<u32, u64>,                    // benchmarked providers hash rates
+    provider_failures: HashMap<u32, u32>, // failovers from providers since they last completed a job
+    quarantined_providers: HashSet<u32>,  // providers which kept failing and are not used for jobs
+    policies: ProviderPolicies,           // jobs scheduling policies of providers classes
+    aborted_jobs: HashMap<u64, AbortJobRequest>, // abort requests applied when an aborted task stops
+    store: Option<JobStore>,                     // persistent jobs store
+    shutting_down: bool, // no jobs are started once the server is shutting down
     job_status_subscribers: HashMap<u64, Sender<Result<JobStatusStreamResponse, Status>>>,
 }
 
+/// Timeout for interrupting in-progress compute cycles when aborting all jobs
+const STOP_PROVIDERS_TIMEOUT_MS: u32 = 5000;
+
 #[async_trait::async_trait]
 impl Actor for PosServer {
     async fn started(&mut self, _ctx: &mut Context<Self>) -> Result<()> {
@@ -45,8 +78,9 @@ impl Service for PosServer {}
 impl Default for PosServer {
     fn default() -> Self {
         PosServer {
+            backend: Arc::new(ScryptBackend::default()),
             providers: vec![],
-            pending_jobs: vec![],
+            pending_jobs: JobsQueue::default(),
             jobs: Default::default(),
             config: Config {
                 data_dir: "./".to_string(),
@@ -56,8 +90,26 @@ impl Default for PosServer {
                 n: 512,
                 r: 1,
                 p: 1,
+                checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+                max_file_size: 0,
+                pow_search_factor: DEFAULT_POW_SEARCH_FACTOR,
+                pow_search_timeout: 0,
+                compute_retries: DEFAULT_COMPUTE_RETRIES,
+                retry_backoff_ms: DEFAULT_RETRY_BACKOFF_MS,
+                quarantine_failures: DEFAULT_QUARANTINE_FAILURES,
             },
             providers_pool: vec![],
+            cancel_signals: HashMap::default(),
+            job_providers: HashMap::default(),
+            tasks: HashMap::default(),
+            disabled_providers: HashSet::default(),
+            hash_rates: HashMap::default(),
+            provider_failures: HashMap::default(),
+            quarantined_providers: HashSet::default(),
+            policies: ProviderPolicies::default(),
+            aborted_jobs: HashMap::default(),
+            store: None,
+            shutting_down: false,
             job_status_subscribers: HashMap::default(),
         }
     }
@@ -65,16 +117,36 @@ impl Default for PosServer {
 
 #[message(result = "Result<()>")]
 pub(crate) struct Init {
+    /// computes jobs with its compute providers
+    pub(crate) backend: Arc<dyn ComputeBackend>,
     /// server base config - must be set when initializing
     pub(crate) use_cpu_provider: bool,
+    /// use both cpu and gpu providers. use_cpu_provider is ignored when set
+    pub(crate) use_all_providers: bool,
+    /// jobs scheduling policies of providers classes
+    pub(crate) policies: ProviderPolicies,
+    /// orders queued jobs
+    pub(crate) scheduler: Box<dyn Scheduler>,
+    /// jobs store directory. Jobs are not persisted when empty
+    pub(crate) jobs_store_dir: String,
+    /// only providers matching one of these filters are used. All providers are used when empty
+    pub(crate) enabled_providers: Vec<ProviderFilter>,
+    /// providers matching one of these filters are not used
+    pub(crate) disabled_providers: Vec<ProviderFilter>,
+    /// number of indexes computed to benchmark each provider. Providers aren't benchmarked when 0
+    pub(crate) benchmark_indexes: u64,
+    /// run the backend's self tests of each provider. Providers which fail them are not used
+    pub(crate) self_test_providers: bool,
 }
 
 /// Init the service
 #[async_trait::async_trait]
 impl Handler<Init> for PosServer {
     async fn handle(&mut self, _ctx: &mut Context<Self>, msg: Init) -> Result<()> {
-        for p in get_providers() {
-            if !msg.use_cpu_provider && p.compute_api == COMPUTE_API_CLASS_CPU {
+        self.backend = msg.backend;
+        for p in self.backend.providers() {
+            let use_cpu_provider = msg.use_cpu_provider || msg.use_all_providers;
+            if !use_cpu_provider && p.compute_api == COMPUTE_API_CLASS_CPU {
                 info!(
                     "skipping cpu provider id: {}, model: {}, compute_api: {}",
                     p.id,
@@ -84,27 +156,268 @@ impl Handler<Init> for PosServer {
                 continue;
             }
 
-            if msg.use_cpu_provider && p.compute_api != COMPUTE_API_CLASS_CPU {
+            if !msg.use_all_providers
+                && msg.use_cpu_provider
+                && p.compute_api != COMPUTE_API_CLASS_CPU
+            {
                 info!("Skipping non-cpu provider. {}: {}", p.id, p.model);
                 continue;
             }
 
-            info!(
-                "Adding to pool provider id: {}, model: {}, compute_api: {}",
-                p.id,
-                p.model,
-                pos_api::api_extensions::get_provider_class_string(p.compute_api)
-            );
-            self.providers_pool.push(p.id);
+            if msg.self_test_providers {
+                match self.backend.self_test(p.id) {
+                    Ok(()) => info!("provider {}: {} passed self tests", p.id, p.model),
+                    Err(e) => {
+                        error!("skipping provider {}: {}. {}", p.id, p.model, e);
+                        continue;
+                    }
+                }
+            }
+
+            if msg.benchmark_indexes > 0 {
+                match self.backend.benchmark(p.id, msg.benchmark_indexes) {
+                    Some(rate) => {
+                        info!("provider {}: {} benchmark: {} h/s", p.id, p.model, rate);
+                        self.hash_rates.insert(p.id, rate);
+                    }
+                    None => error!("failed to benchmark provider {}: {}", p.id, p.model),
+                }
+            }
+
+            let info = self.provider_info(&p);
+            let enabled = (msg.enabled_providers.is_empty()
+                || msg.enabled_providers.iter().any(|f| f.matches(&info)))
+                && !msg.disabled_providers.iter().any(|f| f.matches(&info));
+
+            if enabled {
+                info!(
+                    "Adding to pool provider id: {}, model: {}, compute_api: {}",
+                    p.id,
+                    p.model,
+                    pos_api::api_extensions::get_provider_class_string(p.compute_api)
+                );
+                self.providers_pool.push(p.id);
+            } else {
+                info!("provider {}: {} is disabled by config", p.id, p.model);
+                self.disabled_providers.insert(p.id);
+            }
             self.providers.push(p);
         }
 
-        if self.providers.is_empty() {
+        if self.providers_pool.is_empty() {
             bail!("no compatible compute providers are available on the system.")
         }
+        self.policies = msg.policies;
+        self.pending_jobs.set_scheduler(msg.scheduler);
+
+        // stored jobs are loaded when they are resumed after the config was set
+        if !msg.jobs_store_dir.is_empty() {
+            self.store = Some(JobStore::open(Path::new(msg.jobs_store_dir.as_str()))?);
+        }
+
+        Ok(())
+    }
+}
+
+impl PosServer {
+    /// Load stored jobs from a previous server run.
+    /// Queued jobs are queued again in their queue order. In-progress jobs are stopped with an interrupted error
+    /// and may be continued by clients from their last written index.
+    fn load_jobs(&mut self, store: &JobStore) -> Result<()> {
+        let mut queued_jobs = vec![];
+        for mut job in store.load_all()? {
+            match JobStatus::try_from(job.status) {
+                Ok(JobStatus::Queued) => {
+                    info!("loaded queued job {}", job.id);
+                    queued_jobs.push(job);
+                }
+                Ok(JobStatus::Started) => {
+                    info!("loaded interrupted job {}", job.id);
+                    job.status = JobStatus::Stopped as i32;
+                    job.stopped = datetime::Instant::now().seconds() as u64;
+                    job.compute_provider_id = u32::MAX;
+                    for stripe in job.stripes.iter_mut() {
+                        stripe.compute_provider_id = u32::MAX;
+                    }
+                    job.last_error = Some(JobError {
+                        error: Error::Interrupted as i32,
+                        message: format!("job {}: interrupted by server shutdown", job.id),
+                    });
+                    store.save(&job)?;
+                    self.jobs.insert(job.id, job);
+                }
+                _ => {
+                    self.jobs.insert(job.id, job);
+                }
+            }
+        }
+
+        queued_jobs.sort_by_key(|j| (j.queue_position, j.submitted));
+        for job in queued_jobs {
+            self.pending_jobs.push_back(job);
+        }
+        Ok(())
+    }
 
+    /// Returns the config used to compute a job's data - the server config with the job's compute params
+    /// and data files layout
+    pub(crate) fn job_config(&self, job: &Job) -> Config {
+        let config = match job.params.as_ref() {
+            Some(params) => self.config.with_params(params),
+            None => self.config.clone(),
+        };
+        match job.data_dir.is_empty() {
+            true => config,
+            false => Config {
+                data_dir: job.data_dir.clone(),
+                max_file_size: job.max_file_size,
+                ..config
+            },
+        }
+    }
+
+    /// Persist a job's current state to the jobs store
+    pub(crate) fn store_job(&self, job: &Job) {
+        if let Some(store) = self.store.as_ref() {
+            if let Err(e) = store.save(job) {
+                error!("failed to store job {}: {}", job.id, e);
+            }
+        }
+    }
+
+    /// Persist the queued jobs with their current queue positions
+    fn store_queue(&self) {
+        for job in self.pending_jobs.jobs() {
+            self.store_job(job);
+        }
+    }
+
+    /// Delete a job from the jobs store
+    fn unstore_job(&self, id: u64) {
+        if let Some(store) = self.store.as_ref() {
+            if let Err(e) = store.delete(id) {
+                error!("failed to delete job {} from store: {}", id, e);
+            }
+        }
+    }
+
+    /// Resume interrupted jobs from their checkpoints.
+    /// A job's stripes are reset to their checkpoint's last durable indexes and the job is
+    /// queued to continue its stripes from these indexes. Jobs which can't be recovered stay interrupted.
+    fn resume_interrupted_jobs(&mut self) {
+        let ids: Vec<u64> = self
+            .jobs
+            .values()
+            .filter(|j| {
+                j.status == JobStatus::Stopped as i32
+                    && matches!(&j.last_error, Some(e) if e.error == Error::Interrupted as i32)
+            })
+            .map(|j| j.id)
+            .collect();
+
+        for id in ids {
+            let mut job = self.jobs[&id].clone();
+            match self.recover_job(&mut job) {
+                Ok(()) => {
+                    info!(
+                        "resuming interrupted job {}. {} indexes were written",
+                        job.id,
+                        job.indexes_written()
+                    );
+                    self.jobs.remove(&id);
+                    self.pending_jobs.push(job);
+                    self.store_queue();
+                }
+                Err(e) => info!("can't resume interrupted job {}: {}", id, e),
+            }
+        }
+    }
+
+    /// Recover a job's data from its checkpoint and queue it for execution.
+    /// Data files are truncated to the checkpoint's durable data
+    fn recover_job(&self, job: &mut Job) -> Result<()> {
+        // a pow job has no data and continues its search from its last reported search index
+        if !job.is_pow_only() {
+            let config = self.job_config(job);
+            let checkpoint = Checkpoint::load(&Checkpoint::path(&config.data_dir, job.id))?;
+            checkpoint.verify(job, &config)?;
+
+            job.stripes = checkpoint.job_stripes();
+            DataFiles::new(job, &config)?.recover(&job.stripes)?;
+            job.bits_written = job.indexes_written() * config.bits_per_index as u64;
+            job.add_pow_solutions(&checkpoint.pow_solutions);
+        }
+
+        job.status = JobStatus::Queued as i32;
+        job.last_error = None;
+        job.started = 0;
+        job.stopped = 0;
+        Ok(())
+    }
+
+    /// Start queued jobs on idle providers in queue order.
+    /// A queued job is skipped while no idle provider's class policy admits it.
+    async fn start_queued_jobs(&mut self) -> Result<()> {
+        if self.shutting_down {
+            return Ok(());
+        }
+        while let Some(id) = self
+            .pending_jobs
+            .jobs()
+            .iter()
+            .find(|j| !self.idle_providers_for(j).is_empty())
+            .map(|j| j.id)
+        {
+            let job = self.pending_jobs.remove(id).unwrap();
+            info!("starting queued job {}", job.id);
+            self.start_task(&job).await?;
+            self.store_queue();
+        }
         Ok(())
     }
+
+    /// Returns true iff the provider's class policy and the job's affinity admit the job
+    fn provider_admits(&self, id: u32, job: &Job) -> bool {
+        self.providers.iter().any(|p| {
+            p.id == id
+                && self.policies.admits(
+                    pos_api::api_extensions::get_provider_class_string(p.compute_api),
+                    job,
+                )
+                && match job.affinity.as_ref() {
+                    Some(affinity) => affinity.matches(&self.provider_info(p)),
+                    None => true,
+                }
+        })
+    }
+
+    /// Returns the idle providers which may compute the job
+    pub(crate) fn idle_providers_for(&self, job: &Job) -> Vec<u32> {
+        self.providers_pool
+            .iter()
+            .copied()
+            .filter(|id| self.provider_admits(*id, job))
+            .collect()
+    }
+}
+
+#[message(result = "Result<()>")]
+pub(crate) struct ResumeJobs;
+
+/// Load and resume the jobs of the jobs store - should be called after the config was set so that
+/// the config can be set without forcing it while stored jobs are queued.
+/// Interrupted jobs are recovered from their checkpoints and queued jobs are started on idle providers.
+#[async_trait::async_trait]
+impl Handler<ResumeJobs> for PosServer {
+    async fn handle(&mut self, _ctx: &mut Context<Self>, _msg: ResumeJobs) -> Result<()> {
+        if let Some(store) = self.store.take() {
+            let res = self.load_jobs(&store);
+            self.store = Some(store);
+            res?;
+        }
+        self.resume_interrupted_jobs();
+        self.start_queued_jobs().await
+    }
 }
 
 #[message(result = "Result<Vec<Provider>>")]
@@ -118,15 +431,166 @@ impl Handler<GetAllProviders> for PosServer {
         _ctx: &mut Context<Self>,
         _msg: GetAllProviders,
     ) -> Result<Vec<Provider>> {
-        let mut res = vec![];
-        for p in self.providers.iter() {
-            res.push(Provider {
-                id: p.id,
-                model: p.model.clone(),
-                class: p.compute_api as i32,
-            })
+        Ok(self
+            .providers
+            .iter()
+            .map(|p| self.provider_info(p))
+            .collect())
+    }
+}
+
+impl PosServer {
+    /// Returns a provider's client info
+    fn provider_info(&self, provider: &PosComputeProvider) -> Provider {
+        Provider {
+            id: provider.id,
+            model: provider.model.clone(),
+            class: provider.compute_api as i32,
+            enabled: !self.disabled_providers.contains(&provider.id),
+            hashes_per_sec: self.hash_rates.get(&provider.id).copied().unwrap_or(0),
+            quarantined: self.quarantined_providers.contains(&provider.id),
         }
-        Ok(res)
+    }
+
+    /// Returns the ids of the system providers matching a filter
+    fn filter_providers(&self, filter: &ProviderFilter) -> Result<Vec<u32>> {
+        let ids: Vec<u32> = self
+            .providers
+            .iter()
+            .filter(|p| filter.matches(&self.provider_info(p)))
+            .map(|p| p.id)
+            .collect();
+        if ids.is_empty() {
+            bail!("no compute provider matches {}", filter)
+        }
+        Ok(ids)
+    }
+
+    /// Returns the client info of providers by id
+    fn providers_info(&self, ids: &[u32]) -> Vec<Provider> {
+        self.providers
+            .iter()
+            .filter(|p| ids.contains(&p.id))
+            .map(|p| self.provider_info(p))
+            .collect()
+    }
+}
+
+#[message(result = "Result<Vec<Provider>>")]
+pub(crate) struct EnableProviders(pub(crate) ProviderFilter);
+
+/// Add providers to the providers pool and start queued jobs on them
+#[async_trait::async_trait]
+impl Handler<EnableProviders> for PosServer {
+    async fn handle(
+        &mut self,
+        _ctx: &mut Context<Self>,
+        msg: EnableProviders,
+    ) -> Result<Vec<Provider>> {
+        let ids = self.filter_providers(&msg.0)?;
+        for id in ids.iter() {
+            let disabled = self.disabled_providers.remove(id);
+            let quarantined = self.quarantined_providers.remove(id);
+            if !disabled && !quarantined {
+                continue;
+            }
+            self.provider_failures.remove(id);
+
+            // a busy provider is still in use by a job and returns to the pool when the job stops
+            let busy = self.job_providers.values().any(|p| p.contains(id));
+            if !busy && !self.providers_pool.contains(id) {
+                self.providers_pool.push(*id);
+            }
+            info!("provider {} enabled", id);
+        }
+
+        self.start_queued_jobs().await?;
+        Ok(self.providers_info(&ids))
+    }
+}
+
+#[message(result = "Result<Vec<Provider>>")]
+pub(crate) struct DisableProviders(pub(crate) ProviderFilter);
+
+/// Remove providers from the providers pool. Busy providers are removed when their job stops.
+#[async_trait::async_trait]
+impl Handler<DisableProviders> for PosServer {
+    async fn handle(
+        &mut self,
+        _ctx: &mut Context<Self>,
+        msg: DisableProviders,
+    ) -> Result<Vec<Provider>> {
+        let ids = self.filter_providers(&msg.0)?;
+        if self
+            .providers
+            .iter()
+            .all(|p| ids.contains(&p.id) || self.disabled_providers.contains(&p.id))
+        {
+            bail!("can't disable all compute providers")
+        }
+
+        for id in ids.iter() {
+            if self.disabled_providers.insert(*id) {
+                info!("provider {} disabled", id);
+            }
+        }
+        self.providers_pool.retain(|id| !ids.contains(id));
+        Ok(self.providers_info(&ids))
+    }
+}
+
+#[message(result = "Result<Option<u32>>")]
+pub(crate) struct FailoverProvider {
+    pub(crate) job_id: u64,
+    pub(crate) provider_id: u32,
+}
+
+/// Replace a provider of an in-progress job which failed a compute cycle after all of its retries.
+/// The failed provider is quarantined when it keeps failing and is otherwise released to the providers pool.
+/// Returns the provider which continues the failed provider's stripes: an idle provider which may compute the
+/// job or another provider of the job, or None if there is no such provider.
+#[async_trait::async_trait]
+impl Handler<FailoverProvider> for PosServer {
+    async fn handle(
+        &mut self,
+        _ctx: &mut Context<Self>,
+        msg: FailoverProvider,
+    ) -> Result<Option<u32>> {
+        let id = msg.provider_id;
+        let job = match self.jobs.get(&msg.job_id) {
+            Some(job) => job.clone(),
+            None => bail!("unknown job id {}", msg.job_id),
+        };
+
+        let failures = self.provider_failures.entry(id).or_insert(0);
+        *failures += 1;
+        let threshold = self.config.quarantine_failures;
+        if threshold > 0 && *failures >= threshold {
+            warn!(
+                "provider {} failed {} times - quarantining it",
+                id, *failures
+            );
+            self.quarantined_providers.insert(id);
+        }
+
+        let idle = self.idle_providers_for(&job).into_iter().find(|p| *p != id);
+        let providers = self.job_providers.entry(msg.job_id).or_default();
+        providers.retain(|p| *p != id);
+        let failover_id = match idle {
+            Some(idle_id) => {
+                self.providers_pool.retain(|p| *p != idle_id);
+                providers.push(idle_id);
+                Some(idle_id)
+            }
+            None => providers.first().copied(),
+        };
+
+        if !self.disabled_providers.contains(&id) && !self.quarantined_providers.contains(&id) {
+            self.providers_pool.push(id);
+            self.start_queued_jobs().await?;
+        }
+
+        Ok(failover_id)
     }
 }
 
@@ -138,7 +602,7 @@ pub(crate) struct GetAllJobs;
 impl Handler<GetAllJobs> for PosServer {
     async fn handle(&mut self, _ctx: &mut Context<Self>, _msg: GetAllJobs) -> Result<Vec<Job>> {
         let mut res: Vec<Job> = self.jobs.values().cloned().collect();
-        for job in self.pending_jobs.iter() {
+        for job in self.pending_jobs.jobs() {
             res.push(job.clone())
         }
         Ok(res)
@@ -154,7 +618,7 @@ impl Handler<GetJob> for PosServer {
     async fn handle(&mut self, _ctx: &mut Context<Self>, msg: GetJob) -> Result<Option<Job>> {
         if let Some(job) = self.jobs.get(&msg.0) {
             Ok(Some(job.clone()))
-        } else if let Some(job) = self.pending_jobs.iter().find(|&j| j.id == msg.0) {
+        } else if let Some(job) = self.pending_jobs.get(msg.0) {
             Ok(Some(job.clone()))
         } else {
             Ok(None)
@@ -169,45 +633,84 @@ pub(crate) struct UpdateJobStatus(pub(crate) Job);
 #[async_trait::async_trait]
 impl Handler<UpdateJobStatus> for PosServer {
     async fn handle(&mut self, _ctx: &mut Context<Self>, msg: UpdateJobStatus) -> Result<()> {
-        let updated_job = msg.0;
-        if let Some(_) = self.jobs.get(&updated_job.id) {
+        let mut updated_job = msg.0;
+        if self.shutting_down
+            && !self.aborted_jobs.contains_key(&updated_job.id)
+            && matches!(&updated_job.last_error, Some(e) if e.error == Error::Canceled as i32)
+        {
+            // a job stopped by a shutdown is resumed from its checkpoint when the server restarts
+            updated_job.last_error = Some(JobError {
+                error: Error::Interrupted as i32,
+                message: format!("job {}: interrupted by server shutdown", updated_job.id),
+            });
+        }
+
+        if let Some(job) = self.jobs.get_mut(&updated_job.id) {
             // job is running or stopped
 
+            // update job data
+            *job = updated_job.clone();
+            self.store_job(&updated_job);
+
             if updated_job.status != JobStatus::Started as i32 {
+                // Job stopped or completed - release providers of job to pool
+                let providers = self
+                    .job_providers
+                    .remove(&updated_job.id)
+                    .unwrap_or_default();
                 info!(
-                    "job {} finished. Releasing gpu {} pool",
-                    updated_job.id, updated_job.compute_provider_id
+                    "job {} finished. Releasing gpus {:?} to pool",
+                    updated_job.id, providers
                 );
-                // Job stopped or completed - release provider id of job to pool
-                self.providers_pool.push(updated_job.compute_provider_id);
-
-                // pick a pending job any start it
-                if let Some(new_job) = self.pending_jobs.pop() {
-                    info!("starting queued job {}", new_job.id);
-                    self.start_task(&new_job).await?;
-                } else {
+                let disabled = &self.disabled_providers;
+                let quarantined = &self.quarantined_providers;
+                self.providers_pool.extend(
+                    providers
+                        .iter()
+                        .filter(|id| !disabled.contains(id) && !quarantined.contains(id)),
+                );
+
+                // providers which completed a job are no longer failing
+                if updated_job.status == JobStatus::Completed as i32 {
+                    for id in providers.iter() {
+                        self.provider_failures.remove(id);
+                    }
+                }
+                self.cancel_signals.remove(&updated_job.id);
+                self.tasks.remove(&updated_job.id);
+
+                // apply a deferred abort request now that the job's task stopped
+                if let Some(req) = self.aborted_jobs.remove(&updated_job.id) {
+                    self.cleanup_job(updated_job.id, req.delete_job, req.delete_data);
+                }
+
+                // start pending jobs on the released providers
+                if self.pending_jobs.is_empty() {
                     info!("no queued jobs");
                 }
+                self.start_queued_jobs().await?;
+            }
+        } else if self.pending_jobs.replace(&updated_job) {
+            if let Some(job) = self.pending_jobs.get(updated_job.id) {
+                self.store_job(job);
             }
-            // update job data
-            self.jobs.insert(updated_job.id, updated_job.clone());
-        } else if let Some(idx) = self
-            .pending_jobs
-            .iter()
-            .position(|j| j.id == updated_job.id)
-        {
-            self.pending_jobs.remove(idx);
-            self.pending_jobs.insert(idx, updated_job.clone());
         } else {
             error!("unrecognized job")
         }
 
-        // update all job status subscribers
+        self.notify_job_status_subscribers(&updated_job).await;
+        Ok(())
+    }
+}
+
+impl PosServer {
+    /// Send an updated job status to all job status subscribers
+    async fn notify_job_status_subscribers(&mut self, job: &Job) {
         for sub in self.job_status_subscribers.clone().iter() {
             let res = sub
                 .1
                 .send(Ok(JobStatusStreamResponse {
-                    job: Some(updated_job.clone()),
+                    job: Some(job.clone()),
                 }))
                 .await;
 
@@ -222,8 +725,71 @@ impl Handler<UpdateJobStatus> for PosServer {
                 }
             }
         }
+    }
 
-        Ok(())
+    /// Abort a job. A queued job is stopped immediately. An in-progress job is signaled to stop
+    /// and the server releases its provider when its task reports that it has stopped.
+    /// Returns true if an in-progress job was signaled.
+    async fn abort_job(&mut self, id: u64, req: &AbortJobRequest) -> Result<bool> {
+        if let Some(mut job) = self.pending_jobs.remove(id) {
+            info!("canceling queued job {}", id);
+            job.status = JobStatus::Stopped as i32;
+            job.stopped = datetime::Instant::now().seconds() as u64;
+            job.last_error = Some(JobError {
+                error: Error::Canceled as i32,
+                message: format!("job {}: canceled", id),
+            });
+            self.jobs.insert(id, job.clone());
+            self.store_job(&job);
+            self.store_queue();
+            self.notify_job_status_subscribers(&job).await;
+            self.cleanup_job(id, req.delete_job, req.delete_data);
+            return Ok(false);
+        }
+
+        if let Some(cancel) = self.cancel_signals.get(&id) {
+            info!("aborting in-progress job {}...", id);
+            cancel.store(true, Ordering::Relaxed);
+            self.aborted_jobs.insert(id, req.clone());
+            return Ok(true);
+        }
+
+        if !self.jobs.contains_key(&id) {
+            bail!("unknown job id {}", id)
+        }
+
+        // job already stopped or completed
+        self.cleanup_job(id, req.delete_job, req.delete_data);
+        Ok(false)
+    }
+
+    /// Optionally delete a stopped job and its data files (best effort)
+    fn cleanup_job(&mut self, id: u64, delete_job: bool, delete_data: bool) {
+        if delete_data {
+            if let Some(job) = self.jobs.get(&id) {
+                let config = self.job_config(job);
+                match DataFiles::new(job, &config) {
+                    Ok(files) => files.delete(),
+                    Err(e) => info!("failed to delete job {} data files: {}", id, e),
+                }
+
+                let checkpoint_path = Checkpoint::path(&config.data_dir, id);
+                if let Err(e) = Checkpoint::delete(&checkpoint_path) {
+                    info!("failed to delete {}: {}", checkpoint_path.display(), e);
+                }
+
+                let metadata_path = JobMetadata::path(&config.data_dir, job);
+                if let Err(e) = fs::remove_file(&metadata_path) {
+                    info!("failed to delete {}: {}", metadata_path.display(), e);
+                }
+            }
+        }
+
+        if delete_job {
+            info!("deleting job {}", id);
+            self.jobs.remove(&id);
+            self.unstore_job(id);
+        }
     }
 }
 
@@ -233,9 +799,13 @@ pub(crate) struct AddJob(pub(crate) AddJobRequest);
 #[async_trait::async_trait]
 impl Handler<AddJob> for PosServer {
     async fn handle(&mut self, _ctx: &mut Context<Self>, msg: AddJob) -> Result<Job> {
+        if self.shutting_down {
+            return Err(AddJobError::Unavailable("the server is shutting down".to_string()).into());
+        }
         let data = msg.0;
+        let overrides = data.params.clone().unwrap_or_default();
 
-        let job = Job {
+        let mut job = Job {
             id: rand::random(),
             bits_written: 0,
             size_bits: data.post_size_bits,
@@ -250,20 +820,192 @@ impl Handler<AddJob> for PosServer {
             pow_difficulty: data.pow_difficulty,
             pow_solution_index: u64::MAX,
             compute_pow_solution: data.compute_pow_solution,
+            stripes: vec![],
+            pow_search_index: 0,
+            kind: data.kind,
+            pow_hashes_searched: 0,
+            pow_solutions: vec![],
+            pow_solutions_count: data.pow_solutions_count.max(1),
+            all_pow_solutions: data.all_pow_solutions,
+            params: Some(overrides.or(&ComputeParams::from(&self.config))),
+            priority: data.priority,
+            queue_position: 0,
+            affinity: data.affinity,
+            retries: vec![],
+            data_dir: self.config.data_dir.clone(),
+            max_file_size: self.config.max_file_size,
         };
 
-        if let Err(e) = job.validate(
-            self.config.indexes_per_compute_cycle,
-            self.config.bits_per_index,
-        ) {
+        if job.is_pow_only() {
+            // a pow job searches the indexes of existing pos data from start index
+            job.compute_pow_solution = true;
+            job.pow_search_index = data.start_index;
+        } else if data.start_index != 0 {
+            // continue a stopped job from start index using its existing data file
+            let job_id = data.job_id;
+            let start_index = data.start_index;
+            if job_id == 0 {
+                return Err(AddJobError::InvalidJob(
+                    "job id is required to continue a job from a start index".to_string(),
+                )
+                .into());
+            }
+
+            if self.cancel_signals.contains_key(&job_id) || self.pending_jobs.contains(job_id) {
+                return Err(AddJobError::FailedPrecondition(format!(
+                    "job {} is already queued or in progress",
+                    job_id
+                ))
+                .into());
+            }
+
+            job.id = job_id;
+
+            if let Some(stopped_job) = self.jobs.get(&job_id) {
+                if stopped_job.size_bits != job.size_bits {
+                    return Err(AddJobError::FailedPrecondition(format!(
+                        "job {} size is {} bits. A continued job must have the same size",
+                        job_id, stopped_job.size_bits
+                    ))
+                    .into());
+                }
+
+                // the job's data was computed with the stopped job's params
+                if let Some(params) = stopped_job.params.as_ref() {
+                    job.params = Some(overrides.or(params));
+                    if job.params != stopped_job.params {
+                        return Err(AddJobError::FailedPrecondition(format!(
+                            "job {} data was computed with other compute params. A continued job must have the same params",
+                            job_id
+                        ))
+                        .into());
+                    }
+                }
+
+                // the job's data is in the stopped job's data files
+                if !stopped_job.data_dir.is_empty() {
+                    job.data_dir = stopped_job.data_dir.clone();
+                    job.max_file_size = stopped_job.max_file_size;
+                }
+
+                // continue the stopped job's stripes from their written indexes. The start index
+                // must be the job's written indexes so it can't skip indexes which weren't computed
+                if !stopped_job.stripes.is_empty() {
+                    let indexes_written = stopped_job.indexes_written();
+                    if start_index != indexes_written {
+                        return Err(AddJobError::FailedPrecondition(format!(
+                            "job {} has {} written indexes. A continued job must start from its written indexes",
+                            job_id, indexes_written
+                        ))
+                        .into());
+                    }
+
+                    job.stripes = stopped_job
+                        .stripes
+                        .iter()
+                        .map(|s| JobStripe {
+                            compute_provider_id: u32::MAX,
+                            last_error: None,
+                            ..s.clone()
+                        })
+                        .collect();
+                }
+
+                // keep pow solutions which were found in the existing data
+                if stopped_job.compute_pow_solution {
+                    let solutions: Vec<u64> = stopped_job
+                        .pow_solutions
+                        .iter()
+                        .copied()
+                        .filter(|idx| match job.stripes.is_empty() {
+                            true => *idx < start_index,
+                            false => job
+                                .stripes
+                                .iter()
+                                .any(|s| s.start_index <= *idx && *idx < s.next_index),
+                        })
+                        .collect();
+                    job.add_pow_solutions(&solutions);
+                }
+            }
+
+            let bits_per_index = self.job_config(&job).bits_per_index as u64;
+            job.bits_written = match job.stripes.is_empty() {
+                true => start_index * bits_per_index,
+                false => job.indexes_written() * bits_per_index,
+            };
+        }
+
+        // the job's params must be valid compute params like the config's params
+        let config = self.job_config(&job);
+        if let Err(e) = config.validate() {
+            error!("job can't be added - invalid params: {}, {}", job, e);
+            return Err(AddJobError::InvalidParams(e.to_string()).into());
+        }
+
+        if let Err(e) = job.validate(config.indexes_per_compute_cycle, config.bits_per_index) {
             error!("job can't be added - validation failed: {}, {}", job, e);
-            return Err(e);
+            return Err(AddJobError::InvalidJob(e.to_string()).into());
         }
 
-        if self.providers_pool.is_empty() {
-            // all providers busy with in-progress jobs - queue the job
-            self.pending_jobs.push(job.clone());
-            info!("all providers are busy - queueing job {}...", job.id);
+        if job.is_pow_only() && data.start_index >= job.size_bits / config.bits_per_index as u64 {
+            return Err(AddJobError::InvalidJob(format!(
+                "pow search start index {} is out of the job's indexes range",
+                data.start_index
+            ))
+            .into());
+        }
+
+        if !self
+            .providers
+            .iter()
+            .any(|p| self.provider_admits(p.id, &job))
+        {
+            return Err(AddJobError::FailedPrecondition(format!(
+                "no compute provider may compute a job of {} bits with priority {} and affinity {}",
+                job.size_bits,
+                job.priority,
+                job.affinity.clone().unwrap_or_default()
+            ))
+            .into());
+        }
+
+        let files = DataFiles::new(&job, &config)?;
+        if job.bits_written != 0 {
+            // the data files must hold exactly the job's written indexes
+            let res = match job.stripes.is_empty() {
+                true => files.check(&[JobStripe {
+                    start_index: 0,
+                    end_index: data.start_index - 1,
+                    next_index: data.start_index,
+                    compute_provider_id: u32::MAX,
+                    last_error: None,
+                }]),
+                false => files.check(&job.stripes),
+            };
+            if let Err(e) = res {
+                return Err(AddJobError::FailedPrecondition(format!(
+                    "job {} can't be continued from index {}. {}",
+                    job.id, data.start_index, e
+                ))
+                .into());
+            }
+            info!("continuing job {} from index {}", job.id, data.start_index);
+
+            // the continued job replaces the stopped job
+            self.jobs.remove(&job.id);
+        }
+
+        if self.idle_providers_for(&job).is_empty() {
+            // all providers which may compute the job are busy with in-progress jobs - queue the job
+            let id = job.id;
+            self.pending_jobs.push(job);
+            self.store_queue();
+            let job = self.pending_jobs.get(id).cloned().unwrap();
+            info!(
+                "all providers are busy - queueing job {} at position {}...",
+                job.id, job.queue_position
+            );
             return Ok(job);
         }
 
@@ -272,6 +1014,23 @@ impl Handler<AddJob> for PosServer {
     }
 }
 
+#[message(result = "Result<Vec<Job>>")]
+pub(crate) struct MoveJob {
+    pub(crate) id: u64,
+    pub(crate) position: u32,
+}
+
+/// Move a queued job to a 1-based position in the jobs queue. Returns the queued jobs in queue order
+#[async_trait::async_trait]
+impl Handler<MoveJob> for PosServer {
+    async fn handle(&mut self, _ctx: &mut Context<Self>, msg: MoveJob) -> Result<Vec<Job>> {
+        self.pending_jobs.move_job(msg.id, msg.position)?;
+        info!("moved queued job {} to position {}", msg.id, msg.position);
+        self.store_queue();
+        Ok(self.pending_jobs.jobs().to_vec())
+    }
+}
+
 #[message(result = "Result<(Config)>")]
 pub(crate) struct GetConfig;
 
@@ -286,41 +1045,168 @@ impl Handler<GetConfig> for PosServer {
 #[message(result = "Result<()>")]
 pub(crate) struct AbortJob(pub(crate) AbortJobRequest);
 
-/// Set the pos compute config
+/// Abort a job or all jobs when the request job id is 0
 #[async_trait::async_trait]
 impl Handler<AbortJob> for PosServer {
     async fn handle(&mut self, _ctx: &mut Context<Self>, msg: AbortJob) -> Result<()> {
         let req = msg.0;
 
-        if let Some(_job) = self.jobs.get(&req.id) {
-            // todo: abort on-going job - need to do this via sending a message the blocking task
-
-            if req.delete_data {
-                // todo: attempt to delete all job files in store (best effort)
-            }
+        if req.id != 0 {
+            self.abort_job(req.id, &req).await?;
+            return Ok(());
         }
 
-        if req.delete_job {
-            // remove job
+        info!("aborting all jobs...");
 
-            if let Some(idx) = self.pending_jobs.iter().position(|j| j.id == req.id) {
-                self.pending_jobs.remove(idx);
-            }
-            self.jobs.remove(&req.id);
+        // cancel queued jobs first so stopped tasks don't start them
+        let mut ids: Vec<u64> = self.pending_jobs.jobs().iter().map(|j| j.id).collect();
+        ids.extend(self.jobs.keys());
+
+        let mut in_progress = false;
+        for id in ids {
+            in_progress |= self.abort_job(id, &req).await?;
+        }
+
+        if in_progress {
+            // interrupt current compute cycles instead of waiting for them to complete
+            let backend = self.backend.clone();
+            task::spawn_blocking(move || match backend.stop(STOP_PROVIDERS_TIMEOUT_MS) {
+                Ok(()) => info!("stopped all compute providers"),
+                Err(e) => error!("failed to stop all compute providers: {}", e),
+            });
         }
 
         Ok(())
     }
 }
 
+#[message(result = "Result<Vec<JoinHandle<()>>>")]
+pub(crate) struct Shutdown;
+
+/// Stop all in-progress jobs before the server exits. Returns the handles of the stopped jobs tasks and of the
+/// interruption of their compute cycles so the caller can wait for them before shutting down the compute backend.
+/// Stopped jobs are interrupted so they are resumed on restart, and queued jobs stay queued.
+#[async_trait::async_trait]
+impl Handler<Shutdown> for PosServer {
+    async fn handle(
+        &mut self,
+        _ctx: &mut Context<Self>,
+        _msg: Shutdown,
+    ) -> Result<Vec<JoinHandle<()>>> {
+        info!("shutting down. stopping {} jobs...", self.tasks.len());
+        self.shutting_down = true;
+        for cancel in self.cancel_signals.values() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+
+        let mut handles: Vec<JoinHandle<()>> = self.tasks.drain().map(|(_, h)| h).collect();
+        if !handles.is_empty() {
+            // interrupt current compute cycles instead of waiting for them to complete
+            let backend = self.backend.clone();
+            handles.push(task::spawn_blocking(move || {
+                match backend.stop(STOP_PROVIDERS_TIMEOUT_MS) {
+                    Ok(()) => info!("stopped all compute providers"),
+                    Err(e) => error!("failed to stop all compute providers: {}", e),
+                }
+            }));
+        }
+        Ok(handles)
+    }
+}
+
 #[message(result = "Result<()>")]
-pub(crate) struct SetConfig(pub(crate) Config);
+pub(crate) struct SetConfig {
+    pub(crate) config: Config,
+    /// set the config while jobs are queued or in progress
+    pub(crate) force: bool,
+}
+
+/// The reason a config was not set
+#[derive(Debug)]
+pub(crate) enum SetConfigError {
+    /// jobs are queued or in progress
+    JobsInProgress(usize),
+    /// the config is invalid or its data dir isn't usable
+    InvalidConfig(String),
+}
+
+impl std::fmt::Display for SetConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SetConfigError::JobsInProgress(count) => write!(
+                f,
+                "{} jobs are queued or in progress. Wait for them to stop or force the config",
+                count
+            ),
+            SetConfigError::InvalidConfig(msg) => write!(f, "invalid config: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SetConfigError {}
+
+/// The reason a job was not added
+#[derive(Debug)]
+pub(crate) enum AddJobError {
+    /// the job's compute params merged with the config's params are invalid
+    InvalidParams(String),
+    /// the job's request is invalid
+    InvalidJob(String),
+    /// the job can't be added in the server's current state, e.g. a continued job doesn't match its stopped job
+    FailedPrecondition(String),
+    /// the server doesn't accept jobs
+    Unavailable(String),
+}
+
+impl std::fmt::Display for AddJobError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddJobError::InvalidParams(msg) => write!(f, "invalid job params: {}", msg),
+            AddJobError::InvalidJob(msg) => write!(f, "invalid job: {}", msg),
+            AddJobError::FailedPrecondition(msg) => write!(f, "{}", msg),
+            AddJobError::Unavailable(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AddJobError {}
+
+impl PosServer {
+    /// Create the data dir if needed and check that pos data files can be written to it
+    fn check_data_dir(data_dir: &str) -> Result<()> {
+        fs::create_dir_all(data_dir)?;
+        let path = Path::new(data_dir).join(format!(".write_check_{}", rand::random::<u64>()));
+        fs::write(&path, b"")?;
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+}
 
 /// Set the pos compute config
 #[async_trait::async_trait]
 impl Handler<SetConfig> for PosServer {
     async fn handle(&mut self, _ctx: &mut Context<Self>, msg: SetConfig) -> Result<()> {
-        self.config = msg.0;
+        let jobs = self.pending_jobs.len() + self.cancel_signals.len();
+        if jobs > 0 && !msg.force {
+            return Err(SetConfigError::JobsInProgress(jobs).into());
+        }
+
+        if let Err(e) = msg.config.validate() {
+            return Err(SetConfigError::InvalidConfig(e.to_string()).into());
+        }
+
+        if let Err(e) = PosServer::check_data_dir(&msg.config.data_dir) {
+            let msg = format!("data dir {} isn't writable: {}", msg.config.data_dir, e);
+            return Err(SetConfigError::InvalidConfig(msg).into());
+        }
+
+        if jobs > 0 {
+            info!(
+                "config forced while {} jobs are queued or in progress",
+                jobs
+            );
+        }
+        self.config = msg.config;
         Ok(())
     }
 }
@@ -0,0 +1,157 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::job_error::Error;
+use pos_api::api::{
+    AbortJobRequest, AddJobRequest, GetAllJobsStatusRequest, GetConfigRequest,
+    JobStatusStreamRequest,
+};
+use std::convert::TryInto;
+use tokio_stream::StreamExt;
+
+mod test_helpers;
+
+/// Abort an in-progress cpu job and verify that its provider is released for the next queued job
+#[tokio::test]
+async fn abort_job_test() {
+    const LONG_POST_SIZE_BITS: u64 = 8192 * 1024;
+    const SHORT_POST_SIZE_BITS: u64 = 8192 * 8;
+
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let (mut api_client, guard) = test_helpers::start_server(true).await;
+
+    let mut receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let client_id = hex::decode("1215eda121").unwrap();
+
+    let long_job = api_client
+        .add_job(AddJobRequest {
+            client_id: client_id.clone(),
+            post_size_bits: LONG_POST_SIZE_BITS,
+            start_index: 0,
+            friendly_name: "long job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    // queued behind the long job as the cpu provider is busy
+    let short_job = api_client
+        .add_job(AddJobRequest {
+            client_id,
+            post_size_bits: SHORT_POST_SIZE_BITS,
+            start_index: 0,
+            friendly_name: "short job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    let mut abort_sent = false;
+    let mut long_job_canceled = false;
+    while let Some(res) = receiver.next().await {
+        let job = res.unwrap().job.unwrap();
+        match job.status.try_into().unwrap() {
+            JobStatus::Started if job.id == long_job.id && !abort_sent => {
+                info!("aborting job {}", job);
+                api_client
+                    .abort_job(AbortJobRequest {
+                        id: long_job.id,
+                        delete_job: false,
+                        delete_data: false,
+                    })
+                    .await
+                    .unwrap();
+                abort_sent = true;
+            }
+            JobStatus::Stopped if job.id == long_job.id => {
+                let err = job.last_error.clone().unwrap();
+                assert_eq!(err.error, Error::Canceled as i32, "expected canceled job");
+                assert!(job.bits_written < job.size_bits);
+                info!("job canceled: {}", job);
+                long_job_canceled = true;
+            }
+            JobStatus::Stopped => panic!("💥 job stopped due to error: {}", job),
+            JobStatus::Completed => {
+                assert_eq!(job.id, short_job.id, "aborted job should not complete");
+                info!("🎉 completed. job {}", job);
+                break;
+            }
+            _ => info!("job status: {}", job),
+        }
+    }
+
+    assert!(long_job_canceled, "expected aborted job to stop");
+
+    let jobs = api_client
+        .get_all_jobs_statuses(GetAllJobsStatusRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .jobs;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    test_helpers::delete_pos_files(&jobs, config.data_dir);
+
+    // abort and delete all jobs
+    api_client
+        .abort_job(AbortJobRequest {
+            id: 0,
+            delete_job: true,
+            delete_data: false,
+        })
+        .await
+        .unwrap();
+
+    let jobs = api_client
+        .get_all_jobs_statuses(GetAllJobsStatusRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .jobs;
+
+    assert!(jobs.is_empty(), "expected all jobs to be deleted");
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
@@ -0,0 +1,118 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::Kind;
+use pos_api::api::{AddJobRequest, GetAllJobsStatusRequest, GetConfigRequest};
+use tonic::Code;
+
+mod test_helpers;
+
+/// Rejected jobs are reported with the grpc code of their rejection reason
+#[tokio::test]
+async fn add_job_errors_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let (mut api_client, guard) = test_helpers::start_server(true).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let post_size_bits = 16 * config.indexes_per_compute_cycle * config.bits_per_index as u64;
+    let request = AddJobRequest {
+        client_id: hex::decode("1215eda121").unwrap(),
+        post_size_bits,
+        start_index: 0,
+        friendly_name: "rejected job".to_string(),
+        pow_difficulty: vec![0xff; 32],
+        compute_pow_solution: false,
+        job_id: 0,
+        kind: Kind::Data as i32,
+        pow_solutions_count: 1,
+        all_pow_solutions: false,
+        params: None,
+        priority: 0,
+        affinity: None,
+    };
+
+    let rejected = vec![
+        // client id is too long
+        (
+            AddJobRequest {
+                client_id: vec![0x12; 33],
+                ..request.clone()
+            },
+            Code::InvalidArgument,
+        ),
+        // pow difficulty is too short
+        (
+            AddJobRequest {
+                pow_difficulty: vec![0xff; 16],
+                compute_pow_solution: true,
+                ..request.clone()
+            },
+            Code::InvalidArgument,
+        ),
+        // size isn't a multiple of bytes
+        (
+            AddJobRequest {
+                post_size_bits: post_size_bits + 4,
+                ..request.clone()
+            },
+            Code::InvalidArgument,
+        ),
+        // a continued job's id is missing
+        (
+            AddJobRequest {
+                start_index: 1024,
+                ..request.clone()
+            },
+            Code::InvalidArgument,
+        ),
+        // pow search start index is out of range
+        (
+            AddJobRequest {
+                start_index: post_size_bits,
+                kind: Kind::Pow as i32,
+                ..request.clone()
+            },
+            Code::InvalidArgument,
+        ),
+        // a continued job has no data files
+        (
+            AddJobRequest {
+                start_index: 1024,
+                job_id: rand::random(),
+                ..request.clone()
+            },
+            Code::FailedPrecondition,
+        ),
+    ];
+
+    for (request, code) in rejected {
+        let err = api_client.add_job(request).await.unwrap_err();
+        info!("job rejected: {}", err.message());
+        assert_eq!(err.code(), code, "{}", err.message());
+    }
+
+    // rejected jobs aren't queued
+    let jobs = api_client
+        .get_all_jobs_statuses(GetAllJobsStatusRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .jobs;
+    assert!(jobs.is_empty());
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
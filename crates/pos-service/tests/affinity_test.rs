@@ -0,0 +1,165 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::provider_affinity::Affinity;
+use pos_api::api::{
+    AddJobRequest, GetConfigRequest, GetProvidersRequest, JobStatusStreamRequest, ProviderAffinity,
+};
+use std::convert::TryInto;
+use std::env;
+use tokio_stream::StreamExt;
+use tonic::Code;
+
+mod test_helpers;
+
+/// Pin jobs to a provider and verify that a pinned job waits for its provider
+#[tokio::test]
+async fn affinity_test() {
+    const LONG_POST_SIZE_BITS: u64 = 8192 * 256;
+    const SHORT_POST_SIZE_BITS: u64 = 8192 * 8;
+
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    // providers are benchmarked so jobs can be pinned to a min hash rate
+    let config_path = env::current_dir()
+        .unwrap()
+        .join("tests")
+        .join("affinity_conf.json");
+    let (mut api_client, guard) = test_helpers::start_server_with_config(config_path).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let providers = api_client
+        .get_providers(GetProvidersRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .providers;
+    assert!(providers.iter().all(|p| p.hashes_per_sec > 0));
+    let provider = providers.last().unwrap();
+
+    let mut receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let add_job_request = |name: &str, size_bits: u64, affinity: Affinity| AddJobRequest {
+        client_id: hex::decode("1215eda121").unwrap(),
+        post_size_bits: size_bits,
+        start_index: 0,
+        friendly_name: name.to_string(),
+        pow_difficulty: vec![0xff; 32],
+        compute_pow_solution: false,
+        job_id: 0,
+        kind: Kind::Data as i32,
+        pow_solutions_count: 1,
+        all_pow_solutions: false,
+        params: None,
+        priority: 0,
+        affinity: Some(ProviderAffinity {
+            affinity: Some(affinity),
+        }),
+    };
+
+    // affinities no provider can satisfy are rejected
+    for affinity in [
+        Affinity::ProviderId(u32::MAX),
+        Affinity::MinHashesPerSec(u64::MAX),
+    ]
+    .iter()
+    {
+        let res = api_client
+            .add_job(add_job_request(
+                "unsatisfiable job",
+                SHORT_POST_SIZE_BITS,
+                affinity.clone(),
+            ))
+            .await;
+        assert!(res.is_err(), "expected {:?} to be rejected", affinity);
+        assert_eq!(res.unwrap_err().code(), Code::FailedPrecondition);
+    }
+
+    let mut jobs = vec![];
+    for (name, size_bits) in [
+        ("first pinned job", LONG_POST_SIZE_BITS),
+        ("second pinned job", SHORT_POST_SIZE_BITS),
+    ]
+    .iter()
+    {
+        let job = api_client
+            .add_job(add_job_request(
+                name,
+                *size_bits,
+                Affinity::ProviderId(provider.id),
+            ))
+            .await
+            .unwrap()
+            .into_inner()
+            .job
+            .unwrap();
+        jobs.push(job);
+    }
+
+    // the first job only uses the pinned provider and the second job waits for it
+    assert!(jobs[0]
+        .stripes
+        .iter()
+        .all(|s| s.compute_provider_id == provider.id));
+    assert_eq!(jobs[1].status, JobStatus::Queued as i32);
+
+    let class_job = api_client
+        .add_job(add_job_request(
+            "class job",
+            SHORT_POST_SIZE_BITS,
+            Affinity::Class(provider.class),
+        ))
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+    jobs.push(class_job);
+
+    // the second pinned job starts after the first job completed
+    let mut first_job_completed = false;
+    let mut completed_jobs = 0;
+    while let Some(res) = receiver.next().await {
+        let job = res.unwrap().job.unwrap();
+        match job.status.try_into().unwrap() {
+            JobStatus::Started => {
+                if job.id == jobs[1].id {
+                    assert!(first_job_completed);
+                    assert_eq!(job.compute_provider_id, provider.id);
+                }
+            }
+            JobStatus::Completed => {
+                info!("🎉 completed. job {}", job);
+                first_job_completed |= job.id == jobs[0].id;
+                completed_jobs += 1;
+                if completed_jobs == jobs.len() {
+                    break;
+                }
+            }
+            JobStatus::Stopped => panic!("💥 job stopped due to error: {}", job),
+            JobStatus::Queued => info!("job queued: {}", job),
+        }
+    }
+
+    test_helpers::delete_pos_files(&jobs, config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
@@ -0,0 +1,124 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::{
+    AbortJobRequest, AddJobRequest, GetConfigRequest, GetJobStatusRequest, JobStatusStreamRequest,
+};
+use std::convert::TryInto;
+use std::path::Path;
+use std::{env, fs};
+use tokio_stream::StreamExt;
+
+mod test_helpers;
+
+/// An in-progress job is resumed from its checkpoint after the server was killed
+#[tokio::test]
+async fn checkpoint_test() {
+    const POST_SIZE_BITS: u64 = 8192 * 512;
+
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let config_path = env::current_dir()
+        .unwrap()
+        .join("tests")
+        .join("cpu_jobs_store_conf.json");
+
+    // the server persists jobs at the default jobs store dir
+    let _ = fs::remove_dir_all("./jobs");
+
+    let (mut api_client, guard) = test_helpers::start_server_with_config(config_path.clone()).await;
+
+    let mut receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let job = api_client
+        .add_job(AddJobRequest {
+            client_id: hex::decode("1215eda121").unwrap(),
+            post_size_bits: POST_SIZE_BITS,
+            start_index: 0,
+            friendly_name: "interrupted job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    // kill the server while the job is in progress
+    while let Some(res) = receiver.next().await {
+        let job_status = res.unwrap().job.unwrap();
+        match job_status.status.try_into().unwrap() {
+            JobStatus::Started if job_status.bits_written >= POST_SIZE_BITS / 4 => break,
+            JobStatus::Started => info!("job in progress... {}", job_status),
+            _ => panic!("💥 unexpected job status: {}", job_status),
+        }
+    }
+
+    drop(guard);
+
+    // restarted server should resume the job from its checkpoint
+    let (mut api_client, guard) = test_helpers::start_server_with_config(config_path).await;
+    let receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let resumed_job = api_client
+        .get_job_status(GetJobStatusRequest { id: job.id })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    info!("resumed job: {}", resumed_job);
+    if resumed_job.status != JobStatus::Completed as i32 {
+        test_helpers::job_status_handler(receiver).await;
+    }
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let path = Path::new(config.data_dir.as_str()).join(job.file_name());
+    assert_eq!(path.metadata().unwrap().len(), POST_SIZE_BITS / 8);
+
+    test_helpers::delete_pos_files(&vec![job], config.data_dir);
+
+    api_client
+        .abort_job(AbortJobRequest {
+            id: 0,
+            delete_job: true,
+            delete_data: false,
+        })
+        .await
+        .unwrap();
+
+    let _ = fs::remove_dir_all("./jobs");
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
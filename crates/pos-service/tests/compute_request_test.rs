@@ -0,0 +1,78 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::Kind;
+use pos_api::api::{AddJobRequest, GetConfigRequest};
+
+mod test_helpers;
+
+/// Jobs which compute requests can't be made of are rejected when they are added
+#[tokio::test]
+async fn compute_request_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let (mut api_client, guard) = test_helpers::start_server(true).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let request = AddJobRequest {
+        client_id: vec![0x12; 32],
+        post_size_bits: config.indexes_per_compute_cycle * config.bits_per_index as u64,
+        start_index: 0,
+        friendly_name: "invalid compute request job".to_string(),
+        pow_difficulty: vec![0xff; 32],
+        compute_pow_solution: true,
+        job_id: 0,
+        kind: Kind::Data as i32,
+        pow_solutions_count: 1,
+        all_pow_solutions: false,
+        params: None,
+        priority: 0,
+        affinity: None,
+    };
+
+    let res = api_client
+        .add_job(AddJobRequest {
+            client_id: vec![0x12; 33],
+            ..request.clone()
+        })
+        .await;
+    info!("job with a long client id: {:?}", res);
+    assert!(
+        res.is_err(),
+        "a client id longer than 32 bytes was accepted"
+    );
+
+    let res = api_client
+        .add_job(AddJobRequest {
+            pow_difficulty: vec![0xff; 5],
+            ..request.clone()
+        })
+        .await;
+    info!("job with a short pow difficulty: {:?}", res);
+    assert!(res.is_err(), "a short pow difficulty was accepted");
+
+    let res = api_client
+        .add_job(AddJobRequest {
+            pow_difficulty: vec![],
+            ..request
+        })
+        .await;
+    assert!(
+        res.is_err(),
+        "a pow job without a pow difficulty was accepted"
+    );
+
+    drop(guard);
+}
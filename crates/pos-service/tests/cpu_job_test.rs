@@ -3,8 +3,10 @@ extern crate log;
 extern crate pos_api;
 
 use log::LevelFilter;
+use pos_api::api::job::Kind;
 use pos_api::api::{AddJobRequest, GetConfigRequest, GetProvidersRequest, JobStatusStreamRequest};
-use std::env;
+use std::path::Path;
+use std::{env, fs};
 
 mod test_helpers;
 
@@ -64,6 +66,13 @@ async fn cpu_job_test_no_pow() {
             friendly_name: "world's first pos".to_string(),
             pow_difficulty,
             compute_pow_solution: false,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
         })
         .await
         .unwrap()
@@ -73,6 +82,15 @@ async fn cpu_job_test_no_pow() {
     info!("job info: {}", job);
     test_helpers::job_status_handler(receiver).await;
 
+    // verify the job's metadata file
+    let metadata_path = Path::new(config.data_dir.as_str()).join(job.metadata_file_name());
+    let metadata: serde_json::Value =
+        serde_json::from_slice(&fs::read(&metadata_path).unwrap()).unwrap();
+    assert_eq!(metadata["job_id"], job.id);
+    assert_eq!(metadata["status"], "completed");
+    assert_eq!(metadata["bits_written"], POST_SIZE_BITS);
+    assert_eq!(metadata["files"][0]["name"], job.file_name());
+
     // delete the job's pos file
     test_helpers::delete_pos_files(&vec![job], config.data_dir);
 
@@ -0,0 +1,36 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::GetProvidersRequest;
+use std::env;
+
+mod test_helpers;
+
+/// The server falls back to the scrypt cpu backend when the gpu-setup lib is missing
+#[tokio::test]
+async fn gpu_setup_fallback_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let config_path = env::current_dir()
+        .unwrap()
+        .join("tests")
+        .join("gpu_setup_fallback_conf.json");
+    let (mut api_client, guard) = test_helpers::start_server_with_config(config_path).await;
+
+    let providers = api_client
+        .get_providers(GetProvidersRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .providers;
+    assert_eq!(providers.len(), 1);
+    assert!(providers[0].model.starts_with("rust scrypt cpu"));
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
@@ -3,6 +3,7 @@ extern crate log;
 extern crate pos_api;
 
 use log::LevelFilter;
+use pos_api::api::job::Kind;
 use pos_api::api::{
     AddJobRequest, GetConfigRequest, GetProvidersRequest, JobStatusStreamRequest, SetConfigRequest,
 };
@@ -44,6 +45,7 @@ async fn gpu_job_test_pow() {
     let _ = api_client
         .set_config(SetConfigRequest {
             config: Some(config.clone()),
+            force: false,
         })
         .await
         .unwrap();
@@ -76,6 +78,13 @@ async fn gpu_job_test_pow() {
             friendly_name: "world's first pos".to_string(),
             pow_difficulty,
             compute_pow_solution: true,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
         })
         .await
         .unwrap()
@@ -0,0 +1,96 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::Kind;
+use pos_api::api::{
+    AbortJobRequest, AddJobRequest, Config, GetConfigRequest, JobStatusStreamRequest,
+    SetConfigRequest,
+};
+use std::fs;
+use std::path::Path;
+
+mod test_helpers;
+
+/// A job's data is deleted from the data dir it was written to after the config's data dir changed
+#[tokio::test]
+async fn job_data_dir_test() {
+    const POST_SIZE_BITS: u64 = 8192 * 64;
+
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let (mut api_client, guard) = test_helpers::start_server(true).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let job = api_client
+        .add_job(AddJobRequest {
+            client_id: hex::decode("1215eda121").unwrap(),
+            post_size_bits: POST_SIZE_BITS,
+            start_index: 0,
+            friendly_name: "data dir job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+    assert_eq!(job.data_dir, config.data_dir);
+
+    test_helpers::job_status_handler(receiver).await;
+
+    let path = Path::new(config.data_dir.as_str()).join(job.file_name());
+    assert_eq!(path.metadata().unwrap().len(), POST_SIZE_BITS / 8);
+
+    api_client
+        .set_config(SetConfigRequest {
+            config: Some(Config {
+                data_dir: "./test_other_data_dir".to_string(),
+                ..config.clone()
+            }),
+            force: false,
+        })
+        .await
+        .unwrap();
+
+    api_client
+        .abort_job(AbortJobRequest {
+            id: job.id,
+            delete_job: true,
+            delete_data: true,
+        })
+        .await
+        .unwrap();
+
+    assert!(!path.exists());
+
+    let _ = fs::remove_dir_all("./test_other_data_dir");
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
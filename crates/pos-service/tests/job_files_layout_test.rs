@@ -0,0 +1,154 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::{
+    AbortJobRequest, AddJobRequest, Config, GetConfigRequest, JobStatusStreamRequest,
+    SetConfigRequest,
+};
+use std::convert::TryInto;
+use std::env;
+use std::path::Path;
+use tokio_stream::StreamExt;
+
+mod test_helpers;
+
+/// A stopped job is continued with the data files layout it was submitted with after the config's
+/// max file size changed
+#[tokio::test]
+async fn job_files_layout_test() {
+    const POST_SIZE_BITS: u64 = 8192 * 256;
+
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let config_path = env::current_dir()
+        .unwrap()
+        .join("tests")
+        .join("cpu_split_files_conf.json");
+    let (mut api_client, guard) = test_helpers::start_server_with_config(config_path).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let mut receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let client_id = hex::decode("1215eda121").unwrap();
+
+    let job = api_client
+        .add_job(AddJobRequest {
+            client_id: client_id.clone(),
+            post_size_bits: POST_SIZE_BITS,
+            start_index: 0,
+            friendly_name: "files layout job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+    assert_eq!(job.max_file_size, config.max_file_size);
+
+    // abort the job after some data was written
+    let mut stopped_job = None;
+    while let Some(res) = receiver.next().await {
+        let job_status = res.unwrap().job.unwrap();
+        match job_status.status.try_into().unwrap() {
+            JobStatus::Started if job_status.bits_written > 0 => {
+                let _ = api_client
+                    .abort_job(AbortJobRequest {
+                        id: job.id,
+                        delete_job: false,
+                        delete_data: false,
+                    })
+                    .await;
+            }
+            JobStatus::Stopped => {
+                info!("job stopped: {}", job_status);
+                stopped_job = Some(job_status);
+                break;
+            }
+            JobStatus::Completed => panic!("💥 job completed before it was aborted"),
+            _ => info!("job status: {}", job_status),
+        }
+    }
+
+    // new jobs are written to a single data file
+    api_client
+        .set_config(SetConfigRequest {
+            config: Some(Config {
+                max_file_size: 0,
+                ..config.clone()
+            }),
+            force: false,
+        })
+        .await
+        .unwrap();
+
+    let stopped_job = stopped_job.unwrap();
+    let start_index = stopped_job.bits_written / config.bits_per_index as u64;
+    info!("continuing job {} from index {}", job.id, start_index);
+
+    let continued_job = api_client
+        .add_job(AddJobRequest {
+            client_id,
+            post_size_bits: POST_SIZE_BITS,
+            start_index,
+            friendly_name: "files layout job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: job.id,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+    assert_eq!(continued_job.max_file_size, config.max_file_size);
+
+    test_helpers::job_status_handler(receiver).await;
+
+    // the job's data is only in its split data files
+    let data_dir = Path::new(config.data_dir.as_str());
+    assert!(!data_dir.join(job.file_name()).exists());
+    let files_count = (POST_SIZE_BITS / 8 - 1) / config.max_file_size + 1;
+    let mut total_size = 0;
+    for n in 0..files_count {
+        let path = data_dir.join(job.data_file_name(n));
+        total_size += path.metadata().unwrap().len();
+    }
+    assert_eq!(total_size, POST_SIZE_BITS / 8);
+
+    test_helpers::delete_pos_files(&vec![job], config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
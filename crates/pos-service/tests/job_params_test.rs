@@ -0,0 +1,144 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::{
+    AddJobRequest, ComputeParams, GetConfigRequest, GetJobStatusRequest, JobStatusStreamRequest,
+    SetConfigRequest,
+};
+use std::convert::TryInto;
+use std::fs;
+use std::path::Path;
+use tokio_stream::StreamExt;
+
+mod test_helpers;
+
+/// Jobs are computed with the params they were submitted with even when the config changes
+/// while they are queued
+#[tokio::test]
+async fn job_params_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let (mut api_client, guard) = test_helpers::start_server(true).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let mut receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let post_size_bits = 16 * config.indexes_per_compute_cycle * config.bits_per_index as u64;
+    let overrides = ComputeParams {
+        salt: vec![],
+        n: config.n * 2,
+        r: 0,
+        p: 0,
+        bits_per_index: 0,
+        indexes_per_compute_cycle: config.indexes_per_compute_cycle * 2,
+    };
+
+    let mut jobs = vec![];
+    for params in [Some(overrides.clone()), None].iter() {
+        let job = api_client
+            .add_job(AddJobRequest {
+                client_id: hex::decode("1215eda121").unwrap(),
+                post_size_bits,
+                start_index: 0,
+                friendly_name: "job params".to_string(),
+                pow_difficulty: vec![0xff; 32],
+                compute_pow_solution: false,
+                job_id: 0,
+                kind: Kind::Data as i32,
+                pow_solutions_count: 1,
+                all_pow_solutions: false,
+                params: params.clone(),
+                priority: 0,
+                affinity: None,
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .job
+            .unwrap();
+        jobs.push(job);
+    }
+
+    // unset overrides are taken from the config
+    let params = jobs[0].params.clone().unwrap();
+    assert_eq!(params.n, overrides.n);
+    assert_eq!(
+        params.indexes_per_compute_cycle,
+        overrides.indexes_per_compute_cycle
+    );
+    assert_eq!(params.r, config.r);
+    assert_eq!(params.salt, config.salt);
+    assert_eq!(
+        jobs[1].params.clone().unwrap(),
+        ComputeParams::from(&config)
+    );
+
+    // force a config change while the second job is queued
+    let mut new_config = config.clone();
+    new_config.n = config.n * 4;
+    new_config.salt = vec![0xab; 32];
+    api_client
+        .set_config(SetConfigRequest {
+            config: Some(new_config),
+            force: true,
+        })
+        .await
+        .unwrap();
+
+    let mut completed = 0;
+    while let Some(res) = receiver.next().await {
+        let job = res.unwrap().job.unwrap();
+        match job.status.try_into().unwrap() {
+            JobStatus::Completed => {
+                completed += 1;
+                if completed == jobs.len() {
+                    break;
+                }
+            }
+            JobStatus::Stopped => panic!("💥 job stopped due to error: {}", job),
+            _ => info!("job status: {}", job),
+        }
+    }
+
+    for expected in jobs.iter() {
+        let job = api_client
+            .get_job_status(GetJobStatusRequest { id: expected.id })
+            .await
+            .unwrap()
+            .into_inner()
+            .job
+            .unwrap();
+        assert_eq!(job.bits_written, post_size_bits);
+        assert_eq!(job.params, expected.params);
+
+        // the job's data was computed with its params
+        let params = job.params.clone().unwrap();
+        let metadata_path = Path::new(config.data_dir.as_str()).join(job.metadata_file_name());
+        let metadata: serde_json::Value =
+            serde_json::from_slice(&fs::read(&metadata_path).unwrap()).unwrap();
+        assert_eq!(metadata["n"], params.n);
+        assert_eq!(metadata["salt"], hex::encode(&params.salt));
+    }
+
+    test_helpers::delete_pos_files(&jobs, config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
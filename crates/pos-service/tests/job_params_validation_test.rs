@@ -0,0 +1,80 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::Kind;
+use pos_api::api::{AddJobRequest, ComputeParams, GetAllJobsStatusRequest, GetConfigRequest};
+use tonic::Code;
+
+mod test_helpers;
+
+/// Jobs with compute params which are invalid once merged with the config's params are rejected
+#[tokio::test]
+async fn job_params_validation_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let (mut api_client, guard) = test_helpers::start_server(true).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let invalid_params = vec![
+        ComputeParams {
+            n: 1000,
+            ..ComputeParams::default()
+        },
+        ComputeParams {
+            n: 3,
+            ..ComputeParams::default()
+        },
+        ComputeParams {
+            bits_per_index: 16,
+            ..ComputeParams::default()
+        },
+    ];
+
+    let post_size_bits = 16 * config.indexes_per_compute_cycle * config.bits_per_index as u64;
+    for params in invalid_params {
+        let err = api_client
+            .add_job(AddJobRequest {
+                client_id: hex::decode("1215eda121").unwrap(),
+                post_size_bits,
+                start_index: 0,
+                friendly_name: "invalid job params".to_string(),
+                pow_difficulty: vec![0xff; 32],
+                compute_pow_solution: false,
+                job_id: 0,
+                kind: Kind::Data as i32,
+                pow_solutions_count: 1,
+                all_pow_solutions: false,
+                params: Some(params),
+                priority: 0,
+                affinity: None,
+            })
+            .await
+            .unwrap_err();
+        info!("job rejected: {}", err.message());
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+
+    // rejected jobs aren't queued
+    let jobs = api_client
+        .get_all_jobs_statuses(GetAllJobsStatusRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .jobs;
+    assert!(jobs.is_empty());
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
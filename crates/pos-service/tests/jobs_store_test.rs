@@ -0,0 +1,120 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::{
+    AbortJobRequest, AddJobRequest, GetConfigRequest, GetJobStatusRequest, JobStatusStreamRequest,
+};
+use std::{env, fs};
+
+mod test_helpers;
+
+/// Jobs are persisted across server restarts
+#[tokio::test]
+async fn jobs_store_test() {
+    const POST_SIZE_BITS: u64 = 8192 * 32;
+
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let config_path = env::current_dir()
+        .unwrap()
+        .join("tests")
+        .join("cpu_jobs_store_conf.json");
+
+    // the server persists jobs at the default jobs store dir
+    let _ = fs::remove_dir_all("./jobs");
+
+    let (mut api_client, guard) = test_helpers::start_server_with_config(config_path.clone()).await;
+
+    let receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let job = api_client
+        .add_job(AddJobRequest {
+            client_id: hex::decode("1215eda121").unwrap(),
+            post_size_bits: POST_SIZE_BITS,
+            start_index: 0,
+            friendly_name: "stored job".to_string(),
+            pow_difficulty: vec![
+                0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                0xff, 0xff, 0xff, 0xff,
+            ],
+            compute_pow_solution: true,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    test_helpers::job_status_handler(receiver).await;
+
+    let completed_job = api_client
+        .get_job_status(GetJobStatusRequest { id: job.id })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    // restart the server
+    drop(guard);
+    let (mut api_client, guard) = test_helpers::start_server_with_config(config_path).await;
+
+    let stored_job = api_client
+        .get_job_status(GetJobStatusRequest { id: job.id })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .expect("expected job to be loaded from the jobs store");
+
+    info!("stored job: {}", stored_job);
+    assert_eq!(stored_job.status, JobStatus::Completed as i32);
+    assert_eq!(stored_job.bits_written, completed_job.bits_written);
+    assert_eq!(
+        stored_job.pow_solution_index,
+        completed_job.pow_solution_index
+    );
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    test_helpers::delete_pos_files(&vec![job], config.data_dir);
+
+    // delete all jobs from the store
+    api_client
+        .abort_job(AbortJobRequest {
+            id: 0,
+            delete_job: true,
+            delete_data: false,
+        })
+        .await
+        .unwrap();
+
+    let _ = fs::remove_dir_all("./jobs");
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
@@ -0,0 +1,128 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::{
+    AddJobRequest, GetConfigRequest, GetProvidersRequest, Job, JobStatusStreamRequest,
+};
+use pos_compute::COMPUTE_API_CLASS_CPU;
+use std::convert::TryInto;
+use std::env;
+use tokio_stream::StreamExt;
+
+mod test_helpers;
+
+/// Small jobs are computed by the cpu provider and big jobs by gpu providers
+#[tokio::test]
+async fn mixed_providers_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let config_path = env::current_dir()
+        .unwrap()
+        .join("tests")
+        .join("mixed_providers_conf.json");
+    let (mut api_client, guard) = test_helpers::start_server_with_config(config_path).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let providers = api_client
+        .get_providers(GetProvidersRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .providers;
+    let cpu_providers: Vec<u32> = providers
+        .iter()
+        .filter(|p| p.class as u32 == COMPUTE_API_CLASS_CPU)
+        .map(|p| p.id)
+        .collect();
+    assert!(!cpu_providers.is_empty());
+
+    let cycle_bits = config.indexes_per_compute_cycle * config.bits_per_index as u64;
+    let add_job_request = |name: &str, size_bits: u64| AddJobRequest {
+        client_id: hex::decode("1215eda121").unwrap(),
+        post_size_bits: size_bits,
+        start_index: 0,
+        friendly_name: name.to_string(),
+        pow_difficulty: vec![0xff; 32],
+        compute_pow_solution: false,
+        job_id: 0,
+        kind: Kind::Data as i32,
+        pow_solutions_count: 1,
+        all_pow_solutions: false,
+        params: None,
+        priority: 0,
+        affinity: None,
+    };
+
+    let mut jobs: Vec<Job> = vec![];
+
+    let mut receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    // a small job may only be computed by the cpu provider
+    let small_job = api_client
+        .add_job(add_job_request("small job", 8 * cycle_bits))
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+    assert!(small_job
+        .stripes
+        .iter()
+        .all(|s| cpu_providers.contains(&s.compute_provider_id)));
+    jobs.push(small_job);
+
+    // a big job may only be computed by gpu providers
+    let res = api_client
+        .add_job(add_job_request("big job", 32 * cycle_bits))
+        .await;
+    if cpu_providers.len() == providers.len() {
+        info!("no gpu providers - big job should be rejected");
+        assert!(res.is_err(), "expected big job to be rejected");
+    } else {
+        let big_job = res.unwrap().into_inner().job.unwrap();
+        assert!(big_job
+            .stripes
+            .iter()
+            .all(|s| !cpu_providers.contains(&s.compute_provider_id)));
+        jobs.push(big_job);
+    }
+
+    // wait for all jobs to complete
+    let mut completed_jobs = 0;
+    while let Some(res) = receiver.next().await {
+        let job = res.unwrap().job.unwrap();
+        match job.status.try_into().unwrap() {
+            JobStatus::Completed => {
+                info!("🎉 completed. job {}", job);
+                completed_jobs += 1;
+                if completed_jobs == jobs.len() {
+                    break;
+                }
+            }
+            JobStatus::Stopped => panic!("💥 job stopped due to error: {}", job),
+            _ => info!("job {}", job),
+        }
+    }
+
+    test_helpers::delete_pos_files(&jobs, config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
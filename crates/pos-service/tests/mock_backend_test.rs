@@ -0,0 +1,114 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::compute_retry::Action;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::{
+    AddJobRequest, GetConfigRequest, GetJobStatusRequest, GetProvidersRequest,
+    JobStatusStreamRequest,
+};
+use std::convert::TryInto;
+use std::env;
+use tokio_stream::StreamExt;
+
+mod test_helpers;
+
+/// A job is computed by the mock compute backend and the mock provider's injected failures are retried
+#[tokio::test]
+async fn mock_backend_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let config_path = env::current_dir()
+        .unwrap()
+        .join("tests")
+        .join("mock_backend_conf.json");
+    let (mut api_client, guard) = test_helpers::start_server_with_config(config_path).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let providers = api_client
+        .get_providers(GetProvidersRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .providers;
+    let models: Vec<&str> = providers.iter().map(|p| p.model.as_str()).collect();
+    assert_eq!(models, vec!["mock cpu 0", "mock cpu 1"]);
+
+    let mut receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let post_size_bits = 8 * config.indexes_per_compute_cycle * config.bits_per_index as u64;
+    let job = api_client
+        .add_job(AddJobRequest {
+            client_id: vec![0x12; 32],
+            post_size_bits,
+            start_index: 0,
+            friendly_name: "mock backend job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: true,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    while let Some(res) = receiver.next().await {
+        let job = res.unwrap().job.unwrap();
+        match job.status.try_into().unwrap() {
+            JobStatus::Completed => {
+                info!("🎉 completed. job {}", job);
+                break;
+            }
+            JobStatus::Stopped => panic!("💥 job stopped due to error: {}", job),
+            _ => info!("job {}", job),
+        }
+    }
+
+    let job = api_client
+        .get_job_status(GetJobStatusRequest { id: job.id })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    assert_eq!(job.bits_written, post_size_bits);
+    assert!(job.pow_solution_index != u64::MAX);
+
+    // the failing provider's 2 failures were retried
+    let retries: Vec<_> = job
+        .retries
+        .iter()
+        .filter(|r| r.action == Action::Retry as i32)
+        .collect();
+    assert_eq!(retries.len(), 2);
+    assert!(retries.iter().all(|r| r.provider_id == 1));
+
+    test_helpers::delete_pos_files(&vec![job], config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
@@ -3,7 +3,7 @@ extern crate log;
 extern crate pos_api;
 
 use log::LevelFilter;
-use pos_api::api::job::JobStatus;
+use pos_api::api::job::{JobStatus, Kind};
 use pos_api::api::{
     AddJobRequest, GetAllJobsStatusRequest, GetConfigRequest, JobStatusStreamRequest,
 };
@@ -48,6 +48,13 @@ async fn multiple_jobs_test() {
                 friendly_name: format!("job {}", i),
                 pow_difficulty: pow_difficulty.clone(),
                 compute_pow_solution: true,
+                job_id: 0,
+                kind: Kind::Data as i32,
+                pow_solutions_count: 1,
+                all_pow_solutions: false,
+                params: None,
+                priority: 0,
+                affinity: None,
             })
             .await;
     }
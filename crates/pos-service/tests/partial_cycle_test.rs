@@ -0,0 +1,104 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::Kind;
+use pos_api::api::{AddJobRequest, GetConfigRequest, GetJobStatusRequest, JobStatusStreamRequest};
+use std::fs;
+use std::path::Path;
+
+mod test_helpers;
+
+/// A job which size is not a multiple of a compute cycle computes a final partial cycle
+#[tokio::test]
+async fn partial_cycle_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let (mut api_client, guard) = test_helpers::start_server(true).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    // 10 cycles and 100 additional indexes
+    let post_size_bits =
+        (10 * config.indexes_per_compute_cycle + 100) * config.bits_per_index as u64;
+
+    let receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let client_id = hex::decode("1215eda121").unwrap();
+
+    // sizes which are not made of whole labels can't be represented
+    let res = api_client
+        .add_job(AddJobRequest {
+            client_id: client_id.clone(),
+            post_size_bits: post_size_bits + 4,
+            start_index: 0,
+            friendly_name: "invalid size job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await;
+    assert!(res.is_err(), "expected invalid job size to be rejected");
+
+    let job = api_client
+        .add_job(AddJobRequest {
+            client_id,
+            post_size_bits,
+            start_index: 0,
+            friendly_name: "partial cycle job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    test_helpers::job_status_handler(receiver).await;
+
+    let job = api_client
+        .get_job_status(GetJobStatusRequest { id: job.id })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    assert_eq!(job.bits_written, post_size_bits);
+
+    let path = Path::new(config.data_dir.as_str()).join(job.file_name());
+    assert_eq!(fs::metadata(path).unwrap().len(), post_size_bits / 8);
+
+    test_helpers::delete_pos_files(&vec![job], config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
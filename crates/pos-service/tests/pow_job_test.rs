@@ -0,0 +1,85 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::{AddJobRequest, GetConfigRequest, GetJobStatusRequest, JobStatusStreamRequest};
+use std::path::Path;
+
+mod test_helpers;
+
+/// A pow job searches for a pow solution in its indexes range without writing pos data
+#[tokio::test]
+async fn pow_job_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let (mut api_client, guard) = test_helpers::start_server(true).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let post_size_bits = 4 * config.indexes_per_compute_cycle * config.bits_per_index as u64;
+    let start_index = config.indexes_per_compute_cycle + 10;
+
+    let receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let job = api_client
+        .add_job(AddJobRequest {
+            client_id: hex::decode("1215eda121").unwrap(),
+            post_size_bits,
+            start_index,
+            friendly_name: "pow job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: 0,
+            kind: Kind::Pow as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    test_helpers::job_status_handler(receiver).await;
+
+    let job = api_client
+        .get_job_status(GetJobStatusRequest { id: job.id })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    assert_eq!(job.status, JobStatus::Completed as i32);
+    assert!(job.compute_pow_solution);
+    assert!(job.pow_solution_index >= start_index);
+    assert!(job.pow_solution_index < post_size_bits / config.bits_per_index as u64);
+    assert!(job.pow_hashes_searched > 0);
+    assert_eq!(job.bits_written, 0);
+
+    let path = Path::new(config.data_dir.as_str()).join(job.file_name());
+    assert!(!path.exists(), "a pow job should not write pos data");
+
+    test_helpers::delete_pos_files(&vec![job], config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
@@ -0,0 +1,101 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::job_error::Error;
+use pos_api::api::{AddJobRequest, GetConfigRequest, GetJobStatusRequest, JobStatusStreamRequest};
+use std::convert::TryInto;
+use tokio_stream::StreamExt;
+
+mod test_helpers;
+
+/// A job which pow solution can't be found stops with a pow search limit error after computing its leaves
+#[tokio::test]
+async fn pow_search_limit_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let (mut api_client, guard) = test_helpers::start_server(true).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    assert!(config.pow_search_factor > 0);
+
+    let post_size_bits = 4 * config.indexes_per_compute_cycle * config.bits_per_index as u64;
+
+    let mut receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    // no hash is smaller than a 0 difficulty
+    let job = api_client
+        .add_job(AddJobRequest {
+            client_id: hex::decode("1215eda121").unwrap(),
+            post_size_bits,
+            start_index: 0,
+            friendly_name: "no pow solution job".to_string(),
+            pow_difficulty: vec![0; 32],
+            compute_pow_solution: true,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    while let Some(res) = receiver.next().await {
+        let job = res.unwrap().job.unwrap();
+        match job.status.try_into().unwrap() {
+            JobStatus::Stopped => {
+                info!("job stopped: {}", job);
+                break;
+            }
+            JobStatus::Completed => panic!("💥 job completed without a pow solution: {}", job),
+            _ => info!("job status: {}", job),
+        }
+    }
+
+    let job = api_client
+        .get_job_status(GetJobStatusRequest { id: job.id })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    assert_eq!(job.status, JobStatus::Stopped as i32);
+    assert_eq!(
+        job.last_error.as_ref().unwrap().error,
+        Error::PowSearchLimit as i32
+    );
+    assert_eq!(job.bits_written, post_size_bits);
+    assert_eq!(job.pow_solution_index, u64::MAX);
+    assert_eq!(
+        job.pow_search_index,
+        post_size_bits / config.bits_per_index as u64
+    );
+
+    test_helpers::delete_pos_files(&vec![job], config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
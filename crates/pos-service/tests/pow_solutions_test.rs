@@ -0,0 +1,108 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::{
+    AddJobRequest, GetConfigRequest, GetJobStatusRequest, Job, JobStatusStreamRequest,
+};
+use std::fs;
+use std::path::Path;
+
+mod test_helpers;
+
+/// Jobs which find several pow solutions - all the solutions in a data job's indexes and
+/// the first solutions of a pow job's indexes range
+#[tokio::test]
+async fn pow_solutions_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let (mut api_client, guard) = test_helpers::start_server(true).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let indexes: u64 = 65536;
+    let post_size_bits = indexes * config.bits_per_index as u64;
+    let mut pow_difficulty = vec![0; 32];
+    pow_difficulty[0] = 0x01;
+
+    let mut jobs = vec![];
+    for (kind, count, all) in [(Kind::Data, 0, true), (Kind::Pow, 3, false)].iter() {
+        let receiver = api_client
+            .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+            .await
+            .unwrap()
+            .into_inner();
+
+        let job = api_client
+            .add_job(AddJobRequest {
+                client_id: hex::decode("1215eda121").unwrap(),
+                post_size_bits,
+                start_index: 0,
+                friendly_name: "pow solutions job".to_string(),
+                pow_difficulty: pow_difficulty.clone(),
+                compute_pow_solution: true,
+                job_id: 0,
+                kind: *kind as i32,
+                pow_solutions_count: *count,
+                all_pow_solutions: *all,
+                params: None,
+                priority: 0,
+                affinity: None,
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .job
+            .unwrap();
+
+        test_helpers::job_status_handler(receiver).await;
+
+        let job = api_client
+            .get_job_status(GetJobStatusRequest { id: job.id })
+            .await
+            .unwrap()
+            .into_inner()
+            .job
+            .unwrap();
+
+        assert_eq!(job.status, JobStatus::Completed as i32);
+        assert!(!job.pow_solutions.is_empty());
+        assert!(job.pow_solutions.windows(2).all(|w| w[0] < w[1]));
+        assert!(job.pow_solutions.iter().all(|idx| *idx < indexes));
+        assert_eq!(job.pow_solution_index, job.pow_solutions[0]);
+        jobs.push(job);
+    }
+
+    // all the data job's solutions are in its metadata
+    let data_job: &Job = &jobs[0];
+    let metadata_path = Path::new(config.data_dir.as_str()).join(data_job.metadata_file_name());
+    let metadata: serde_json::Value =
+        serde_json::from_slice(&fs::read(&metadata_path).unwrap()).unwrap();
+    assert_eq!(
+        metadata["pow_solutions"],
+        serde_json::json!(data_job.pow_solutions)
+    );
+
+    // the pow job finds the first 3 solutions of the data job's indexes
+    let pow_job: &Job = &jobs[1];
+    assert_eq!(pow_job.pow_solutions.len(), 3);
+    if data_job.pow_solutions.len() >= 3 {
+        assert_eq!(pow_job.pow_solutions[..], data_job.pow_solutions[..3]);
+    }
+
+    test_helpers::delete_pos_files(&jobs, config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
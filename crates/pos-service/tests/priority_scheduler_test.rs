@@ -0,0 +1,159 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::{
+    AbortJobRequest, AddJobRequest, GetConfigRequest, JobStatusStreamRequest, MoveJobRequest,
+};
+use std::convert::TryInto;
+use std::env;
+use tokio_stream::StreamExt;
+
+mod test_helpers;
+
+/// Queue jobs with priorities, move a queued job and verify that queued jobs start in queue order
+#[tokio::test]
+async fn priority_scheduler_test() {
+    const LONG_POST_SIZE_BITS: u64 = 8192 * 1024;
+    const SHORT_POST_SIZE_BITS: u64 = 8192 * 8;
+
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let config_path = env::current_dir()
+        .unwrap()
+        .join("tests")
+        .join("priority_scheduler_conf.json");
+    let (mut api_client, guard) = test_helpers::start_server_with_config(config_path).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let mut receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let add_job_request = |name: &str, size_bits: u64, priority: u32| AddJobRequest {
+        client_id: hex::decode("1215eda121").unwrap(),
+        post_size_bits: size_bits,
+        start_index: 0,
+        friendly_name: name.to_string(),
+        pow_difficulty: vec![0xff; 32],
+        compute_pow_solution: false,
+        job_id: 0,
+        kind: Kind::Data as i32,
+        pow_solutions_count: 1,
+        all_pow_solutions: false,
+        params: None,
+        priority,
+        affinity: None,
+    };
+
+    // the long job keeps all the providers busy while other jobs are queued
+    let long_job = api_client
+        .add_job(add_job_request("long job", LONG_POST_SIZE_BITS, 0))
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+    assert_eq!(long_job.queue_position, 0);
+
+    let mut queued_jobs = vec![];
+    for (name, priority) in [("low 1", 0), ("low 2", 0), ("high", 5)].iter() {
+        let job = api_client
+            .add_job(add_job_request(name, SHORT_POST_SIZE_BITS, *priority))
+            .await
+            .unwrap()
+            .into_inner()
+            .job
+            .unwrap();
+        assert_eq!(job.status, JobStatus::Queued as i32);
+        queued_jobs.push(job);
+    }
+
+    // the high priority job is queued before the low priority jobs
+    assert_eq!(queued_jobs[0].queue_position, 1);
+    assert_eq!(queued_jobs[1].queue_position, 2);
+    assert_eq!(queued_jobs[2].queue_position, 1);
+
+    // move the last low priority job to the head of the queue
+    let queue = api_client
+        .move_job(MoveJobRequest {
+            id: queued_jobs[1].id,
+            position: 1,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .jobs;
+    let expected_order = vec![queued_jobs[1].id, queued_jobs[2].id, queued_jobs[0].id];
+    assert_eq!(
+        queue.iter().map(|j| j.id).collect::<Vec<u64>>(),
+        expected_order
+    );
+    for (i, job) in queue.iter().enumerate() {
+        assert_eq!(job.queue_position, i as u32 + 1);
+    }
+
+    // only queued jobs may be moved
+    assert!(api_client
+        .move_job(MoveJobRequest {
+            id: long_job.id,
+            position: 1,
+        })
+        .await
+        .is_err());
+
+    // release the providers to the queued jobs
+    api_client
+        .abort_job(AbortJobRequest {
+            id: long_job.id,
+            delete_job: true,
+            delete_data: true,
+        })
+        .await
+        .unwrap();
+
+    let mut started_order = vec![];
+    let mut completed_jobs = 0;
+    while let Some(res) = receiver.next().await {
+        let job = res.unwrap().job.unwrap();
+        if job.id == long_job.id {
+            continue;
+        }
+        match job.status.try_into().unwrap() {
+            JobStatus::Started => {
+                if !started_order.contains(&job.id) {
+                    started_order.push(job.id);
+                }
+            }
+            JobStatus::Completed => {
+                info!("🎉 completed. job {}", job);
+                completed_jobs += 1;
+                if completed_jobs == queued_jobs.len() {
+                    break;
+                }
+            }
+            JobStatus::Stopped => panic!("💥 job stopped due to error: {}", job),
+            JobStatus::Queued => info!("job queued: {}", job),
+        }
+    }
+    assert_eq!(started_order, expected_order);
+
+    test_helpers::delete_pos_files(&queued_jobs, config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
@@ -0,0 +1,123 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::Kind;
+use pos_api::api::provider_filter::Filter;
+use pos_api::api::{
+    AddJobRequest, DisableProviderRequest, EnableProviderRequest, GetConfigRequest,
+    GetProvidersRequest, JobStatusStreamRequest, ProviderFilter,
+};
+
+mod test_helpers;
+
+/// Disabled providers are not used for jobs until they are enabled
+#[tokio::test]
+async fn providers_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let (mut api_client, guard) = test_helpers::start_server(true).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let providers = api_client
+        .get_providers(GetProvidersRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .providers;
+    assert!(providers.iter().all(|p| p.enabled));
+
+    // at least one provider must be enabled
+    let res = api_client
+        .disable_provider(DisableProviderRequest {
+            filter: Some(ProviderFilter {
+                filter: Some(Filter::Model("*".to_string())),
+            }),
+        })
+        .await;
+    assert!(res.is_err(), "expected disabling all providers to fail");
+
+    if providers.len() < 2 {
+        info!("skipping disabled provider job - test requires at least 2 providers");
+        info!("{}", guard.0.id());
+        return;
+    }
+
+    let disabled_id = providers[0].id;
+    let id_filter = ProviderFilter {
+        filter: Some(Filter::Id(disabled_id)),
+    };
+    let disabled = api_client
+        .disable_provider(DisableProviderRequest {
+            filter: Some(id_filter.clone()),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .providers;
+    assert_eq!(disabled.len(), 1);
+    assert!(!disabled[0].enabled);
+
+    let receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let job = api_client
+        .add_job(AddJobRequest {
+            client_id: hex::decode("1215eda121").unwrap(),
+            post_size_bits: 16 * config.indexes_per_compute_cycle * config.bits_per_index as u64,
+            start_index: 0,
+            friendly_name: "enabled providers job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    assert_eq!(job.stripes.len(), providers.len() - 1);
+    assert!(job
+        .stripes
+        .iter()
+        .all(|s| s.compute_provider_id != disabled_id));
+
+    test_helpers::job_status_handler(receiver).await;
+
+    let enabled = api_client
+        .enable_provider(EnableProviderRequest {
+            filter: Some(id_filter),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .providers;
+    assert_eq!(enabled.len(), 1);
+    assert!(enabled[0].enabled);
+
+    test_helpers::delete_pos_files(&vec![job], config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
@@ -0,0 +1,120 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::{AbortJobRequest, AddJobRequest, GetConfigRequest, JobStatusStreamRequest};
+use std::convert::TryInto;
+use std::path::Path;
+use tokio_stream::StreamExt;
+
+mod test_helpers;
+
+/// Abort an in-progress cpu job and continue it from the last written index
+#[tokio::test]
+async fn resume_job_test() {
+    const POST_SIZE_BITS: u64 = 8192 * 256;
+
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let (mut api_client, guard) = test_helpers::start_server(true).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let mut receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let client_id = hex::decode("1215eda121").unwrap();
+
+    let job = api_client
+        .add_job(AddJobRequest {
+            client_id: client_id.clone(),
+            post_size_bits: POST_SIZE_BITS,
+            start_index: 0,
+            friendly_name: "resumed job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    // abort the job after some data was written
+    let mut stopped_job = None;
+    while let Some(res) = receiver.next().await {
+        let job_status = res.unwrap().job.unwrap();
+        match job_status.status.try_into().unwrap() {
+            JobStatus::Started if job_status.bits_written > 0 => {
+                let _ = api_client
+                    .abort_job(AbortJobRequest {
+                        id: job.id,
+                        delete_job: false,
+                        delete_data: false,
+                    })
+                    .await;
+            }
+            JobStatus::Stopped => {
+                info!("job stopped: {}", job_status);
+                stopped_job = Some(job_status);
+                break;
+            }
+            JobStatus::Completed => panic!("💥 job completed before it was aborted"),
+            _ => info!("job status: {}", job_status),
+        }
+    }
+
+    let stopped_job = stopped_job.unwrap();
+    let start_index = stopped_job.bits_written / config.bits_per_index as u64;
+    info!("continuing job {} from index {}", job.id, start_index);
+
+    let _ = api_client
+        .add_job(AddJobRequest {
+            client_id,
+            post_size_bits: POST_SIZE_BITS,
+            start_index,
+            friendly_name: "resumed job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: job.id,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap();
+
+    test_helpers::job_status_handler(receiver).await;
+
+    let path = Path::new(config.data_dir.as_str()).join(job.file_name());
+    assert_eq!(path.metadata().unwrap().len(), POST_SIZE_BITS / 8);
+
+    test_helpers::delete_pos_files(&vec![job], config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
@@ -0,0 +1,168 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::pos_data_service_client::PosDataServiceClient;
+use pos_api::api::{AbortJobRequest, AddJobRequest, GetConfigRequest, JobStatusStreamRequest};
+use std::convert::TryInto;
+use std::path::Path;
+use std::{env, fs};
+use tokio_stream::StreamExt;
+use tonic::transport::Channel;
+use tonic::Code;
+
+mod test_helpers;
+
+const POST_SIZE_BITS: u64 = 8192 * 32;
+
+/// Returns a data job request which continues a job from start index
+fn job_request(job_id: u64, start_index: u64) -> AddJobRequest {
+    AddJobRequest {
+        client_id: hex::decode("1215eda121").unwrap(),
+        post_size_bits: POST_SIZE_BITS,
+        start_index,
+        friendly_name: "striped job".to_string(),
+        pow_difficulty: vec![0xff; 32],
+        compute_pow_solution: false,
+        job_id,
+        kind: Kind::Data as i32,
+        pow_solutions_count: 1,
+        all_pow_solutions: false,
+        params: None,
+        priority: 0,
+        affinity: None,
+    }
+}
+
+/// Add a job and wait for it to complete
+async fn complete_job(
+    api_client: &mut PosDataServiceClient<Channel>,
+    request: AddJobRequest,
+) -> u64 {
+    let receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let job = api_client
+        .add_job(request)
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    test_helpers::job_status_handler(receiver).await;
+    job.id
+}
+
+/// A stopped job which is split to several stripes is continued only from its written indexes
+/// and its continued data is the same as the data of a job which wasn't stopped
+#[tokio::test]
+async fn resume_stripes_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let config_path = env::current_dir()
+        .unwrap()
+        .join("tests")
+        .join("mock_stripes_conf.json");
+    let (mut api_client, guard) = test_helpers::start_server_with_config(config_path).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let mut receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let job = api_client
+        .add_job(job_request(0, 0))
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+    assert_eq!(job.stripes.len(), 2, "expected a stripe per provider");
+
+    // abort the job after all of its stripes wrote some data
+    let mut stopped_job = None;
+    while let Some(res) = receiver.next().await {
+        let job_status = res.unwrap().job.unwrap();
+        match job_status.status.try_into().unwrap() {
+            JobStatus::Started if job_status.stripes.iter().all(|s| s.indexes_written() > 0) => {
+                let _ = api_client
+                    .abort_job(AbortJobRequest {
+                        id: job.id,
+                        delete_job: false,
+                        delete_data: false,
+                    })
+                    .await;
+            }
+            JobStatus::Stopped => {
+                info!("job stopped: {}", job_status);
+                stopped_job = Some(job_status);
+                break;
+            }
+            JobStatus::Completed => panic!("💥 job completed before it was aborted"),
+            _ => info!("job status: {}", job_status),
+        }
+    }
+    drop(receiver);
+
+    let stopped_job = stopped_job.unwrap();
+    let start_index = stopped_job.indexes_written();
+    assert_eq!(
+        start_index,
+        stopped_job.bits_written / config.bits_per_index as u64
+    );
+    let last_index = stopped_job
+        .stripes
+        .iter()
+        .map(|s| s.next_index)
+        .max()
+        .unwrap();
+    assert!(start_index < last_index);
+
+    // the indexes before the last written index weren't all computed
+    let res = api_client.add_job(job_request(job.id, last_index)).await;
+    assert_eq!(res.unwrap_err().code(), Code::FailedPrecondition);
+    let res = api_client
+        .add_job(job_request(job.id, start_index - 8))
+        .await;
+    assert_eq!(res.unwrap_err().code(), Code::FailedPrecondition);
+
+    info!("continuing job {} from index {}", job.id, start_index);
+    let job_id = complete_job(&mut api_client, job_request(job.id, start_index)).await;
+    assert_eq!(job_id, job.id);
+
+    // the continued job's data is the same as the data of a job which wasn't stopped
+    let other_job_id = complete_job(&mut api_client, job_request(0, 0)).await;
+
+    let data_dir = Path::new(config.data_dir.as_str());
+    let data = fs::read(data_dir.join(job.file_name())).unwrap();
+    let other_job = pos_api::api::Job {
+        id: other_job_id,
+        ..job.clone()
+    };
+    let other_data = fs::read(data_dir.join(other_job.file_name())).unwrap();
+    assert_eq!(data.len() as u64, POST_SIZE_BITS / 8);
+    assert!(data == other_data, "continued job data is different");
+
+    test_helpers::delete_pos_files(&vec![job, other_job], config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
@@ -0,0 +1,112 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::{
+    AddJobRequest, GetConfigRequest, GetJobStatusRequest, GetProvidersRequest,
+    JobStatusStreamRequest,
+};
+use std::convert::TryInto;
+use std::path::Path;
+use std::{env, fs};
+use tokio_stream::StreamExt;
+
+mod test_helpers;
+
+/// A job is computed by the pure rust scrypt backend. Its labels and pow solution match scrypt hashes of
+/// id || position (8 bytes, little endian) with the default salt and n=64, r=1, p=1 which were computed independently
+#[tokio::test]
+async fn scrypt_backend_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let config_path = env::current_dir()
+        .unwrap()
+        .join("tests")
+        .join("scrypt_backend_conf.json");
+    let (mut api_client, guard) = test_helpers::start_server_with_config(config_path).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let providers = api_client
+        .get_providers(GetProvidersRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .providers;
+    assert_eq!(providers.len(), 1);
+    assert!(providers[0].model.starts_with("rust scrypt cpu"));
+
+    let mut receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let mut pow_difficulty = vec![0; 32];
+    pow_difficulty[0] = 0x08;
+    let post_size_bits = config.indexes_per_compute_cycle * config.bits_per_index as u64;
+    let job = api_client
+        .add_job(AddJobRequest {
+            client_id: vec![0x12; 32],
+            post_size_bits,
+            start_index: 0,
+            friendly_name: "scrypt backend job".to_string(),
+            pow_difficulty,
+            compute_pow_solution: true,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    while let Some(res) = receiver.next().await {
+        let job = res.unwrap().job.unwrap();
+        match job.status.try_into().unwrap() {
+            JobStatus::Completed => {
+                info!("🎉 completed. job {}", job);
+                break;
+            }
+            JobStatus::Stopped => panic!("💥 job stopped due to error: {}", job),
+            _ => info!("job {}", job),
+        }
+    }
+
+    let job = api_client
+        .get_job_status(GetJobStatusRequest { id: job.id })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    assert_eq!(job.bits_written, post_size_bits);
+    assert_eq!(job.pow_solution_index, 13);
+
+    let data = fs::read(Path::new(config.data_dir.as_str()).join(job.file_name())).unwrap();
+    assert_eq!(data.len() as u64, post_size_bits / 8);
+    assert_eq!(hex::encode(&data[..16]), "b4f3724496320a18b91e64ed0e0640bc");
+
+    test_helpers::delete_pos_files(&vec![job], config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
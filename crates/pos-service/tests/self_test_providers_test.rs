@@ -0,0 +1,36 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::GetProvidersRequest;
+use std::env;
+
+mod test_helpers;
+
+/// Providers which fail the backend's self tests are not used
+#[tokio::test]
+async fn self_test_providers_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let config_path = env::current_dir()
+        .unwrap()
+        .join("tests")
+        .join("self_test_providers_conf.json");
+    let (mut api_client, guard) = test_helpers::start_server_with_config(config_path).await;
+
+    let providers = api_client
+        .get_providers(GetProvidersRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .providers;
+    let models: Vec<&str> = providers.iter().map(|p| p.model.as_str()).collect();
+    assert_eq!(models, vec!["mock cpu 0"]);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
@@ -0,0 +1,153 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::{JobStatus, Kind};
+use pos_api::api::{
+    AbortJobRequest, AddJobRequest, Config, GetConfigRequest, JobStatusStreamRequest,
+    SetConfigRequest,
+};
+use std::convert::TryInto;
+use tokio_stream::StreamExt;
+use tonic::Code;
+
+mod test_helpers;
+
+/// Invalid configs are rejected and the config can only be changed while jobs are running when forced
+#[tokio::test]
+async fn set_config_test() {
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let (mut api_client, guard) = test_helpers::start_server(true).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let invalid_configs = vec![
+        Config {
+            salt: vec![0xab; 16],
+            ..config.clone()
+        },
+        Config {
+            n: 1000,
+            ..config.clone()
+        },
+        Config {
+            r: 0,
+            ..config.clone()
+        },
+        Config {
+            p: 0,
+            ..config.clone()
+        },
+        Config {
+            bits_per_index: 4,
+            ..config.clone()
+        },
+        Config {
+            indexes_per_compute_cycle: 0,
+            ..config.clone()
+        },
+        Config {
+            // a data dir in a file can't be created
+            data_dir: "Cargo.toml/pos".to_string(),
+            ..config.clone()
+        },
+    ];
+
+    for invalid_config in invalid_configs {
+        let err = api_client
+            .set_config(SetConfigRequest {
+                config: Some(invalid_config),
+                force: false,
+            })
+            .await
+            .unwrap_err();
+        info!("config rejected: {}", err.message());
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+
+    let mut receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let job = api_client
+        .add_job(AddJobRequest {
+            client_id: hex::decode("1215eda121").unwrap(),
+            post_size_bits: 8192 * 1024,
+            start_index: 0,
+            friendly_name: "long job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    let err = api_client
+        .set_config(SetConfigRequest {
+            config: Some(config.clone()),
+            force: false,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+
+    api_client
+        .set_config(SetConfigRequest {
+            config: Some(config.clone()),
+            force: true,
+        })
+        .await
+        .unwrap();
+
+    api_client
+        .abort_job(AbortJobRequest {
+            id: job.id,
+            delete_job: false,
+            delete_data: true,
+        })
+        .await
+        .unwrap();
+
+    while let Some(res) = receiver.next().await {
+        let job = res.unwrap().job.unwrap();
+        if job.status.try_into() == Ok(JobStatus::Stopped) {
+            break;
+        }
+    }
+
+    // no jobs are running
+    api_client
+        .set_config(SetConfigRequest {
+            config: Some(config.clone()),
+            force: false,
+        })
+        .await
+        .unwrap();
+
+    test_helpers::delete_pos_files(&vec![job], config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
@@ -0,0 +1,97 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::Kind;
+use pos_api::api::{AddJobRequest, GetConfigRequest, JobStatusStreamRequest};
+use std::path::Path;
+use std::{env, fs};
+
+mod test_helpers;
+
+/// A job's data is split into numbered files listed in the job's metadata manifest
+#[tokio::test]
+async fn split_files_test() {
+    const POST_SIZE_BITS: u64 = 8192 * 64;
+
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let config_path = env::current_dir()
+        .unwrap()
+        .join("tests")
+        .join("cpu_split_files_conf.json");
+    let (mut api_client, guard) = test_helpers::start_server_with_config(config_path).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    assert_eq!(config.max_file_size, 10000);
+
+    let receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let job = api_client
+        .add_job(AddJobRequest {
+            client_id: hex::decode("1215eda121").unwrap(),
+            post_size_bits: POST_SIZE_BITS,
+            start_index: 0,
+            friendly_name: "split files job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    test_helpers::job_status_handler(receiver).await;
+
+    let data_dir = Path::new(config.data_dir.as_str());
+    let metadata_path = data_dir.join(job.metadata_file_name());
+    let metadata: serde_json::Value =
+        serde_json::from_slice(&fs::read(&metadata_path).unwrap()).unwrap();
+
+    // 65536 bytes of data in 10000 bytes files
+    let files = metadata["files"].as_array().unwrap();
+    assert_eq!(files.len(), 7);
+
+    let mut next_index = 0;
+    let mut total_size = 0;
+    for (n, file) in files.iter().enumerate() {
+        assert_eq!(file["name"], job.data_file_name(n as u64));
+        assert_eq!(file["start_index"], next_index);
+        let size = fs::metadata(data_dir.join(job.data_file_name(n as u64)))
+            .unwrap()
+            .len();
+        assert_eq!(file["size_bytes"], size);
+        assert!(size <= config.max_file_size);
+        next_index = file["end_index"].as_u64().unwrap() + 1;
+        total_size += size;
+    }
+    assert_eq!(total_size, POST_SIZE_BITS / 8);
+
+    test_helpers::delete_pos_files(&vec![job], config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
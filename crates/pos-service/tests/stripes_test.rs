@@ -0,0 +1,107 @@
+#[macro_use]
+extern crate log;
+extern crate pos_api;
+
+use log::LevelFilter;
+use pos_api::api::job::Kind;
+use pos_api::api::{AddJobRequest, GetConfigRequest, GetJobStatusRequest, GetProvidersRequest};
+use pos_api::api::{JobStatusStreamRequest, JobStripe};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+mod test_helpers;
+
+/// A job is split to stripes which are computed in parallel by all idle providers
+#[tokio::test]
+async fn stripes_test() {
+    const POST_SIZE_BITS: u64 = 8192 * 1024;
+
+    let _ = env_logger::builder()
+        .is_test(false)
+        .filter_level(LevelFilter::Info)
+        .try_init();
+
+    let (mut api_client, guard) = test_helpers::start_server(true).await;
+
+    let config = api_client
+        .get_config(GetConfigRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .config
+        .unwrap();
+
+    let providers = api_client
+        .get_providers(GetProvidersRequest {})
+        .await
+        .unwrap()
+        .into_inner()
+        .providers;
+
+    let receiver = api_client
+        .subscribe_job_status_stream(JobStatusStreamRequest { id: 0 })
+        .await
+        .unwrap()
+        .into_inner();
+
+    let job = api_client
+        .add_job(AddJobRequest {
+            client_id: hex::decode("1215eda121").unwrap(),
+            post_size_bits: POST_SIZE_BITS,
+            start_index: 0,
+            friendly_name: "striped job".to_string(),
+            pow_difficulty: vec![0xff; 32],
+            compute_pow_solution: false,
+            job_id: 0,
+            kind: Kind::Data as i32,
+            pow_solutions_count: 1,
+            all_pow_solutions: false,
+            params: None,
+            priority: 0,
+            affinity: None,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    assert_eq!(
+        job.stripes.len(),
+        providers.len(),
+        "expected a stripe per provider"
+    );
+
+    test_helpers::job_status_handler(receiver).await;
+
+    let job = api_client
+        .get_job_status(GetJobStatusRequest { id: job.id })
+        .await
+        .unwrap()
+        .into_inner()
+        .job
+        .unwrap();
+
+    assert_eq!(job.bits_written, POST_SIZE_BITS);
+    assert!(job.stripes.iter().all(JobStripe::is_completed));
+
+    // stripes are computed by different providers and cover the job's indexes range
+    let stripe_providers: HashSet<u32> =
+        job.stripes.iter().map(|s| s.compute_provider_id).collect();
+    assert_eq!(stripe_providers.len(), job.stripes.len());
+    let mut next_index = 0;
+    for stripe in job.stripes.iter() {
+        assert_eq!(stripe.start_index, next_index);
+        next_index = stripe.end_index + 1;
+    }
+    assert_eq!(next_index * config.bits_per_index as u64, POST_SIZE_BITS);
+
+    let path = Path::new(config.data_dir.as_str()).join(job.file_name());
+    assert_eq!(fs::metadata(path).unwrap().len(), POST_SIZE_BITS / 8);
+
+    test_helpers::delete_pos_files(&vec![job], config.data_dir);
+
+    // prevent the compiler from dropping guard before end of test
+    info!("{}", guard.0.id());
+}
@@ -6,7 +6,7 @@ use pos_api::api::job::JobStatus;
 use pos_api::api::pos_data_service_client::PosDataServiceClient;
 use pos_api::api::{Job, JobStatusStreamResponse};
 use std::convert::TryInto;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command};
 use std::time::Duration;
 use std::{env, fs};
@@ -31,6 +31,7 @@ impl Drop for Guard {
 }
 
 /// Start a pos server and return grpc client for it
+#[allow(dead_code)]
 pub async fn start_server(use_cpu_provider: bool) -> (PosDataServiceClient<Channel>, Guard) {
     let tests_path = env::current_dir().unwrap().join("tests");
 
@@ -45,6 +46,14 @@ pub async fn start_server(use_cpu_provider: bool) -> (PosDataServiceClient<Chann
         }
     };
 
+    start_server_with_config(config_path).await
+}
+
+/// Start a pos server with a config file and return grpc client for it
+#[allow(dead_code)]
+pub async fn start_server_with_config(
+    config_path: PathBuf,
+) -> (PosDataServiceClient<Channel>, Guard) {
     info!("Server config file path: {:?}", config_path);
 
     let server_path = "../../target/debug/pos-service";
@@ -65,12 +74,23 @@ pub async fn start_server(use_cpu_provider: bool) -> (PosDataServiceClient<Chann
 }
 
 /// Delete generated pos files for jobs at the provided data dir
+#[allow(dead_code)]
 pub fn delete_pos_files(jobs: &Vec<Job>, data_dir: String) {
     for job in jobs {
-        let file_name = job.file_name();
-        let path = Path::new(data_dir.clone().as_str()).join(file_name);
-        info!("deleting post file {}...", path.display());
-        let _ = fs::remove_file(path).unwrap();
+        let _ = fs::remove_file(Path::new(data_dir.as_str()).join(job.file_name()));
+        // a split job's data files are named <job_id>_<n>.pos
+        let prefix = format!("{}_", job.id);
+        for entry in fs::read_dir(data_dir.as_str()).unwrap() {
+            let path = entry.unwrap().path();
+            let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+            if file_name.starts_with(&prefix) && file_name.ends_with(".pos") {
+                info!("deleting post file {}...", path.display());
+                fs::remove_file(path).unwrap();
+            }
+        }
+        let _ = fs::remove_file(Path::new(data_dir.as_str()).join(job.metadata_file_name()));
+        let _ =
+            fs::remove_file(Path::new(data_dir.as_str()).join(format!("{}.checkpoint", job.id)));
     }
 }
 